@@ -0,0 +1,82 @@
+//! Allocation counting used by the `alloc-count` feature.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Number of allocations made through a `CountingAllocator`.
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+/// Whether a `CountingAllocator` has served at least one allocation.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// A global allocator wrapper that counts allocations for `Timer`.
+///
+/// Install it in the binary to have `time_end` report how many allocations happened
+/// while each timer was running:
+///
+/// ```
+/// use std::alloc::System;
+/// use timelog::CountingAllocator;
+///
+/// #[global_allocator]
+/// static GLOBAL: CountingAllocator<System> = CountingAllocator::new(System);
+/// # fn main() {}
+/// ```
+///
+/// The count is process-wide, so allocations made by other threads while a timer is
+/// running are included.
+pub struct CountingAllocator<A = System> {
+    inner: A,
+}
+
+impl<A> CountingAllocator<A> {
+    /// Wraps an allocator so its allocations are counted.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The allocator that serves the allocations.
+    ///
+    /// # Returns
+    ///
+    /// Returns the counting wrapper.
+    pub const fn new(inner: A) -> Self {
+        CountingAllocator { inner }
+    }
+}
+
+impl<A> CountingAllocator<A> {
+    fn count(&self) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        if !INSTALLED.load(Ordering::Relaxed) {
+            INSTALLED.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.count();
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.count();
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.count();
+        self.inner.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+}
+
+/// Returns the number of allocations counted so far, or `None` if no `CountingAllocator`
+/// is installed.
+pub(crate) fn allocations() -> Option<u64> {
+    INSTALLED
+        .load(Ordering::Relaxed)
+        .then(|| ALLOCATIONS.load(Ordering::Relaxed))
+}
@@ -0,0 +1,105 @@
+//! A `Timer` shared across async tasks, used by the `tokio` feature.
+
+use crate::{Timer, TimerStats};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// A cloneable handle to a `Timer` guarded by an async-aware mutex.
+///
+/// Unlike `std::sync::Mutex`, waiting for the lock yields to the executor instead of blocking
+/// a worker thread, so contended timing never stalls other tasks. Clones share the same timers.
+#[derive(Clone, Default)]
+pub struct AsyncTimer {
+    inner: Arc<Mutex<Timer>>,
+}
+
+impl AsyncTimer {
+    /// Creates a new `AsyncTimer` instance.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `AsyncTimer` wrapping an empty `Timer`.
+    pub fn new() -> Self {
+        Self::from_timer(Timer::new())
+    }
+
+    /// Wraps an existing `Timer`.
+    ///
+    /// # Arguments
+    ///
+    /// * `timer` - The timer to share.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `AsyncTimer` owning `timer`.
+    pub fn from_timer(timer: Timer) -> Self {
+        AsyncTimer {
+            inner: Arc::new(Mutex::new(timer)),
+        }
+    }
+
+    /// Starts a new timer. See `Timer::time`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    pub async fn time(&self, label: &str) {
+        self.inner.lock().await.time(label);
+    }
+
+    /// Logs and prints the current time of a timer without stopping it. See `Timer::time_log`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    pub async fn time_log(&self, label: &str, silent: bool) -> f64 {
+        self.inner.lock().await.time_log(label, silent)
+    }
+
+    /// Ends a timer and prints its runtime. See `Timer::time_end`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    pub async fn time_end(&self, label: &str, silent: bool) -> f64 {
+        self.inner.lock().await.time_end(label, silent)
+    }
+
+    /// Returns a copy of the accumulated statistics of a label. See `Timer::stats`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the label's statistics, or `None` if the label has never been ended.
+    pub async fn stats(&self, label: &str) -> Option<TimerStats> {
+        self.inner.lock().await.stats(label).copied()
+    }
+
+    /// Runs a closure with exclusive access to the underlying `Timer`.
+    ///
+    /// This gives access to the full synchronous API while holding the lock once.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The closure to run.
+    ///
+    /// # Returns
+    ///
+    /// Returns the closure's result.
+    pub async fn with_timer<R>(&self, f: impl FnOnce(&mut Timer) -> R) -> R {
+        f(&mut *self.inner.lock().await)
+    }
+}
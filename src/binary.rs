@@ -0,0 +1,103 @@
+//! Compact binary encoding helpers used by the `binary` feature.
+//!
+//! Values are written back to back without field names: integers and floats as little-endian
+//! bytes of their width, strings and sequences prefixed with their length.
+//! The layout is simple enough that no serialization library is needed.
+
+use std::io::{Error, ErrorKind, Result};
+
+/// Magic bytes and format version at the start of every encoded report.
+pub(crate) const MAGIC: &[u8; 4] = b"TLB2";
+
+/// Appends a `u64` to `out`.
+pub(crate) fn put_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Appends a `u128` to `out`.
+pub(crate) fn put_u128(out: &mut Vec<u8>, value: u128) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Appends an `f64` to `out`.
+pub(crate) fn put_f64(out: &mut Vec<u8>, value: f64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Appends a length-prefixed UTF-8 string to `out`.
+pub(crate) fn put_str(out: &mut Vec<u8>, value: &str) {
+    put_u64(out, value.len() as u64);
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// Reads values written by the `put_*` helpers, failing with `InvalidData` on malformed input.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a reader, checking that `bytes` start with `MAGIC`.
+    pub(crate) fn new(bytes: &'a [u8]) -> Result<Self> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("not a timelog binary report"));
+        }
+        Ok(reader)
+    }
+
+    /// Reads a `u64`.
+    pub(crate) fn u64(&mut self) -> Result<u64> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Reads a `u128`.
+    pub(crate) fn u128(&mut self) -> Result<u128> {
+        let bytes = self.take(16)?;
+        Ok(u128::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Reads an `f64`.
+    pub(crate) fn f64(&mut self) -> Result<f64> {
+        let bytes = self.take(8)?;
+        Ok(f64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Reads a length-prefixed UTF-8 string.
+    pub(crate) fn string(&mut self) -> Result<String> {
+        let len = self.len()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("label is not valid UTF-8"))
+    }
+
+    /// Reads a length prefix, rejecting lengths longer than the remaining input.
+    pub(crate) fn len(&mut self) -> Result<usize> {
+        let len = self.u64()?;
+        match usize::try_from(len) {
+            Ok(len) if len <= self.bytes.len() => Ok(len),
+            _ => Err(invalid("length exceeds the input")),
+        }
+    }
+
+    /// Fails unless the whole input was read.
+    pub(crate) fn finish(self) -> Result<()> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid("trailing bytes after the report"))
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(invalid("unexpected end of input"));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
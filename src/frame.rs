@@ -0,0 +1,29 @@
+//! Per-frame collections of completed timings, for game and simulation loops.
+
+/// The timings completed during one frame, returned by `Timer::end_frame`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FrameReport {
+    /// The zero-based number of the frame.
+    pub frame: u64,
+    /// Every timing completed during the frame as `(label, ms)`, in completion order.
+    pub timings: Vec<(String, f64)>,
+}
+
+impl FrameReport {
+    /// Returns the summed time of a label during the frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the total in milliseconds, or `None` if the label didn't complete this frame.
+    pub fn total_ms(&self, label: &str) -> Option<f64> {
+        self.timings
+            .iter()
+            .filter(|(name, _)| name == label)
+            .map(|&(_, ms)| ms)
+            .reduce(|a, b| a + b)
+    }
+}
@@ -11,6 +11,8 @@
 //! - Convert durations to milliseconds
 //! - End timers and get elapsed time
 //! - Singleton instance for global timing
+//! - Accumulated per-label statistics and closure benchmarking
+//! - `LocalTimer` for single-threaded code and `SharedTimer` for sharing across threads
 //!
 //! ## Usage
 //!
@@ -20,6 +22,8 @@
 //! ## Example
 //!
 //! ```
+//! use timelog::Timer;
+//!
 //! let mut timer = Timer::new();
 //! timer.time("operation");
 //! // Perform some operation
@@ -27,39 +31,353 @@
 //! println!("Operation took {} ms", elapsed);
 //!
 //! // End a timer
-//! let final_time = timer.time_end("operation");
+//! let final_time = timer.time_end("operation", false);
 //! println!("Final time: {} ms", final_time);
 //!
 //! // Use singleton instance
 //! Timer::single_instance().time("global_operation");
 //! // Perform global operation
-//! Timer::single_instance().time_end("global_operation");
+//! Timer::single_instance().time_end("global_operation", false);
 //! ```
 //!
+//! ## Cargo features
+//!
+//! - `json`: JSON reports, Chrome tracing export and appending completed timings to a
+//!   newline-delimited JSON file.
+//! - `prometheus`: OpenMetrics histogram export with trace ID exemplars.
+//! - `binary`: a compact binary encoding of the accumulated statistics.
+//! - `statsd`: send completed timings to a StatsD agent over UDP.
+//! - `tokio`: an `AsyncTimer` backed by `tokio::sync::Mutex`.
+//! - `alloc-count`: count allocations per timer with a `CountingAllocator`.
+//! - `self-metrics`: measure time spent waiting on the `SharedTimer` lock.
+//! - `macros`: the `#[timed]` attribute, which times every call of a function under its name
+//!   in `SharedTimer::global()`.
+//! - `approx-quantiles`: streaming per-label percentile estimates in bounded memory.
+//! - `num-format`: configurable thousands and decimal separators for printed values.
+//! - `log`: also log timings classified by `set_thresholds` at the matching `log` level.
+//! - `disabled`: compile timing down to no-ops, e.g. for release builds. The API is unchanged,
+//!   so call sites need no `cfg`, but `Instant::now()` is never called: timers are never
+//!   started, measurements return `0.0`, lookups return `None`, and `bench` returns empty
+//!   statistics without running the closure.
+//!
 //! This library is useful for performance monitoring and optimization in Rust applications.
 //! The `time_end` method allows you to stop a timer and get its final elapsed time.
 //! The `single_instance` feature provides a global Timer instance for convenient timing across your application.
 
+// Lets `#[timed]`-generated `::timelog::` paths resolve inside this crate too.
+extern crate self as timelog;
+
+use std::borrow::Cow;
+use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
-use std::sync::Once;
+#[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+use std::fs::File;
+use std::hash::BuildHasher;
+use std::panic::Location;
 #[cfg(not(target_arch = "wasm32"))]
-use std::time::{Duration, Instant};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::Sender;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Mutex;
+use std::sync::{Once, RwLock};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Instant, SystemTime};
 #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
 use wasm_bindgen::prelude::*;
 #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
 use web_sys::{window, Performance};
 
+#[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+mod alloc_count;
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+mod async_timer;
+#[cfg(feature = "binary")]
+mod binary;
+mod frame;
+#[cfg(feature = "json")]
+mod json;
+mod lock;
+#[cfg(not(target_arch = "wasm32"))]
+mod measurement;
+mod millis;
+#[cfg(not(target_arch = "wasm32"))]
+mod output;
+#[cfg(all(feature = "prometheus", not(target_arch = "wasm32")))]
+mod prometheus;
+#[cfg(feature = "approx-quantiles")]
+mod quantiles;
+mod scope;
+mod session;
+mod shared;
+#[cfg(not(target_arch = "wasm32"))]
+mod state;
+mod stats;
+#[cfg(all(feature = "statsd", not(target_arch = "wasm32")))]
+mod statsd;
+#[cfg(not(target_arch = "wasm32"))]
+mod timed_iter;
+mod timing;
+
+#[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+pub use alloc_count::CountingAllocator;
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+pub use async_timer::AsyncTimer;
+pub use frame::FrameReport;
+pub use lock::TimedMutexGuard;
+#[cfg(not(target_arch = "wasm32"))]
+pub use measurement::{CompletedMeasurement, Measurement};
+pub use millis::{Millis, TimeUnit};
+pub use scope::ScopeGuard;
+#[doc(hidden)]
+pub use scope::TimedGuard;
+pub use session::Session;
+pub use shared::SharedTimer;
+#[cfg(not(target_arch = "wasm32"))]
+pub use state::{Regression, TimerState};
+pub use stats::TimerStats;
+#[cfg(not(target_arch = "wasm32"))]
+pub use timed_iter::TimedIter;
+#[cfg(feature = "macros")]
+pub use timelog_macros::timed;
+pub use timing::{NoopTimer, Timing};
+
+/// Starts a timer and records the call site, printed as `label (file:line): 12.000ms` on end.
+///
+/// ```
+/// use timelog::{time_here, Timer};
+///
+/// let mut timer = Timer::new();
+/// time_here!(timer, "parse");
+/// timer.time_end("parse", false);
+/// ```
+#[macro_export]
+macro_rules! time_here {
+    ($timer:expr, $label:expr) => {
+        $timer.time_here($label)
+    };
+}
+
+/// The lock-free `Timer`, for single-threaded hot paths.
+///
+/// Use a `SharedTimer` when timers must be shared across threads.
+pub type LocalTimer = Timer;
+
+/// The first instant the crate was touched, used by `Timer::since_process_start`.
+#[cfg(not(target_arch = "wasm32"))]
+static PROCESS_START: std::sync::OnceLock<Instant> = std::sync::OnceLock::new();
+
+/// Whether timing is compiled in; `false` when the `disabled` feature is enabled.
+const ENABLED: bool = !cfg!(feature = "disabled");
+
 /// A struct for timing and logging time durations.
 ///
 /// `Timer` uses a `HashMap` to store multiple named timers, each associated with a label.
-pub struct Timer {
+/// The HashMap hashes labels with `S`, the standard SipHash by default; see `Timer::with_hasher`.
+pub struct Timer<S = RandomState> {
     /// HashMap storing timers, where keys are labels and values are start times.
     #[cfg(not(target_arch = "wasm32"))]
-    timers: HashMap<String, Instant>,
+    timers: HashMap<String, Instant, S>,
     #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
-    timers: HashMap<String, f64>,
+    timers: HashMap<String, f64, S>,
     #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
     performance: Performance,
+    /// Factor applied to every reported elapsed value.
+    scale: f64,
+    /// Smallest elapsed value reported, hiding precision the platform clock doesn't have.
+    #[cfg(not(target_arch = "wasm32"))]
+    resolution_floor: Duration,
+    /// Reference instant that `time_end` reports the end offset from, set by `set_epoch`.
+    #[cfg(not(target_arch = "wasm32"))]
+    epoch: Option<Instant>,
+    /// Whether label usage is being tracked for `usage_report`.
+    track_usage: bool,
+    /// Labels passed to `time_end` without a matching `time`, in call order.
+    unmatched_ends: Vec<String>,
+    /// Function mapping a stored label to the name it is printed under.
+    display_mapper: Option<LabelNormalizer>,
+    /// Function applied to every label before it is stored or looked up.
+    label_normalizer: Option<LabelNormalizer>,
+    /// Maximum label length in characters, with the policy for longer labels.
+    max_label_len: Option<(usize, LabelLengthPolicy)>,
+    /// Elapsed milliseconds at the last `time_log_delta` call, keyed by label.
+    last_logged: HashMap<String, f64>,
+    /// Instant at which the most recent timer was ended.
+    #[cfg(not(target_arch = "wasm32"))]
+    last_end: Option<Instant>,
+    /// The highest number of timers that were running at the same time.
+    peak_concurrent: usize,
+    /// Number of labels generated by `time_auto` so far.
+    auto_labels: u64,
+    /// Timings completed during the current frame, or `None` outside a frame.
+    frame: Option<Vec<(String, f64)>>,
+    /// Number of frames ended so far.
+    frames: u64,
+    /// Whether starting a running label nests instead of restarting it.
+    reentrant: bool,
+    /// Number of nested starts of running reentrant timers, keyed by label.
+    depths: HashMap<String, usize>,
+    /// Per-label call counters used by `time_every_n`.
+    sample_counters: HashMap<String, u64>,
+    /// Accumulated statistics of completed measurements, keyed by label.
+    stats: HashMap<String, TimerStats>,
+    /// Whether individual measurements are retained in `samples`.
+    retain_samples: bool,
+    /// Retained measurements in milliseconds, keyed by label.
+    samples: HashMap<String, Vec<f64>>,
+    /// Streaming quantile estimates of completed measurements, keyed by label.
+    #[cfg(feature = "approx-quantiles")]
+    quantiles: HashMap<String, quantiles::QuantileSketch>,
+    /// Instants at which paused timers were paused, keyed by label.
+    #[cfg(not(target_arch = "wasm32"))]
+    paused: HashMap<String, Instant>,
+    /// Time running timers spent paused before their last resume, keyed by label.
+    #[cfg(not(target_arch = "wasm32"))]
+    paused_total: HashMap<String, Duration>,
+    /// Whether `time_end` reports the share of a paused timer's lifetime spent paused.
+    #[cfg(not(target_arch = "wasm32"))]
+    show_idle: bool,
+    /// Parent labels of nested scopes, keyed by child label.
+    parents: HashMap<String, String>,
+    /// Labels of the scope guards currently alive on this timer, innermost last.
+    scopes: Vec<String>,
+    /// Call sites of timers started with `time_here!`, keyed by label.
+    locations: HashMap<String, &'static Location<'static>>,
+    /// Wall-clock start times of running timers, keyed by label, while `track_skew` is set.
+    #[cfg(not(target_arch = "wasm32"))]
+    wall_starts: HashMap<String, SystemTime>,
+    /// Whether `time` captures wall-clock start times for `time_end_with_skew`.
+    #[cfg(not(target_arch = "wasm32"))]
+    track_skew: bool,
+    /// Tags attached to running timers, keyed by label.
+    tags: HashMap<String, Vec<(String, String)>>,
+    /// The latest traced observation of each label as `(ms, trace_id, unix_seconds)`.
+    #[cfg(all(feature = "prometheus", not(target_arch = "wasm32")))]
+    exemplars: HashMap<String, (f64, String, f64)>,
+    /// Categories of categorized timers, keyed by label.
+    categories: HashMap<String, String>,
+    /// Categories whose timers print, or `None` to print every category.
+    category_filter: Option<Vec<String>>,
+    /// Time budgets in milliseconds, keyed by label.
+    budgets: HashMap<String, f64>,
+    /// Reference times in milliseconds that completed timings are compared to, keyed by label.
+    baselines: HashMap<String, f64>,
+    /// Weights applied by `top_by_weighted_total`, keyed by label.
+    weights: HashMap<String, f64>,
+    /// Best times in milliseconds of previous runs, keyed by label.
+    best: HashMap<String, f64>,
+    /// Separator inserted between groups of three integer digits of printed values.
+    #[cfg(feature = "num-format")]
+    thousands_separator: Option<char>,
+    /// Separator between the integer and fractional digits of printed values.
+    #[cfg(feature = "num-format")]
+    decimal_separator: char,
+    /// Warn and error thresholds in milliseconds that classify completed timings.
+    thresholds: Option<(f64, f64)>,
+    /// Event counters, kept separately from timers.
+    counters: RwLock<HashMap<String, AtomicU64>>,
+    /// Allocation counts at the start of running timers, keyed by label.
+    #[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+    alloc_starts: HashMap<String, u64>,
+    /// Allocations made during the last completed measurement, keyed by label.
+    #[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+    allocations: HashMap<String, u64>,
+    /// How often the missing-timer warning is printed for a label.
+    #[cfg(not(target_arch = "wasm32"))]
+    missing_warning: MissingTimerWarning,
+    /// Instants at which the missing-timer warning was last printed, keyed by label.
+    #[cfg(not(target_arch = "wasm32"))]
+    warned: Mutex<HashMap<String, Instant>>,
+    /// Destination and buffering of printed lines.
+    #[cfg(not(target_arch = "wasm32"))]
+    output: Mutex<output::Output>,
+    /// Channel that completed measurements are pushed onto.
+    #[cfg(not(target_arch = "wasm32"))]
+    channel: Option<Sender<(String, Duration)>>,
+    /// StatsD client that completed timings are sent to.
+    #[cfg(all(feature = "statsd", not(target_arch = "wasm32")))]
+    statsd: Option<statsd::StatsdClient>,
+    /// File that completed timings are appended to as newline-delimited JSON.
+    #[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+    ndjson_sink: Option<File>,
+    /// Recorded trace events as `(label, start_us, duration_us, thread_id)`, or `None` when
+    /// trace recording is off.
+    #[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+    trace: Option<Vec<(String, u128, u128, u64)>>,
+}
+
+/// A function mapping a label to the form it is stored and looked up under.
+pub type LabelNormalizer = Box<dyn Fn(&str) -> String + Send + Sync>;
+
+/// What `Timer::time` does with a label longer than the configured maximum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelLengthPolicy {
+    /// Cut the label down to the maximum length and start the timer under the shortened label.
+    Truncate,
+    /// Refuse to start the timer.
+    Reject,
+}
+
+/// How often `Timer` prints the "Timer does not exist" warning for a label.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingTimerWarning {
+    /// Print the warning every time a missing label is used.
+    Always,
+    /// Print the warning only the first time a missing label is used.
+    Once,
+    /// Print the warning for a label at most once per interval.
+    RateLimited(Duration),
+}
+
+/// The severity band of a completed timing, set by `Timer::set_thresholds`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The timing is below the warn threshold.
+    Ok,
+    /// The timing is at or above the warn threshold but below the error threshold.
+    Warn,
+    /// The timing is at or above the error threshold.
+    Error,
+}
+
+impl Severity {
+    /// Returns the marker that prefixes output lines of this severity, e.g. `[WARN]`.
+    pub fn marker(self) -> &'static str {
+        match self {
+            Severity::Ok => "[OK]",
+            Severity::Warn => "[WARN]",
+            Severity::Error => "[ERROR]",
+        }
+    }
+
+    /// Returns the `log` level that output lines of this severity are logged at.
+    #[cfg(feature = "log")]
+    pub fn log_level(self) -> log::Level {
+        match self {
+            Severity::Ok => log::Level::Info,
+            Severity::Warn => log::Level::Warn,
+            Severity::Error => log::Level::Error,
+        }
+    }
+}
+
+/// A report of label usage, produced by `Timer::usage_report`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageReport {
+    /// Labels that were started but never ended, sorted alphabetically.
+    pub leaked: Vec<String>,
+    /// Labels that were ended without being started, in call order.
+    pub unmatched_ends: Vec<String>,
+}
+
+impl UsageReport {
+    /// Returns `true` if every started label was ended and every ended label was started.
+    pub fn is_clean(&self) -> bool {
+        self.leaked.is_empty() && self.unmatched_ends.is_empty()
+    }
 }
 
 impl Timer {
@@ -69,115 +387,225 @@ impl Timer {
     ///
     /// Returns a new `Timer` instance with an empty timer HashMap.
     pub fn new() -> Self {
-        #[cfg(not(target_arch = "wasm32"))]
-        return Timer {
-            timers: HashMap::new(),
-        };
-
-        #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
-        return Timer {
-            timers: HashMap::new(),
-            performance: window().unwrap().performance().unwrap(),
-        };
-
-        #[cfg(all(target_arch = "wasm32", feature = "webworker"))]
-        return Timer {
-            timers: HashMap::new(),
-        };
+        Self::with_capacity(0)
     }
 
-    /// Starts a new timer.
+    /// Creates a new `Timer` instance with room for at least `capacity` timers.
+    ///
+    /// Pre-allocating avoids rehashing the internal HashMap while many timers are started.
     ///
     /// # Arguments
     ///
-    /// * `label` - The label for the timer.
-    pub fn time(&mut self, label: &str) {
-        #[cfg(not(target_arch = "wasm32"))]
-        self.timers.insert(label.to_string(), Instant::now());
-
-        #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
-        self.timers
-            .insert(label.to_string(), self.performance.now());
+    /// * `capacity` - The number of concurrent timers to reserve space for.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `Timer` instance with an empty, pre-allocated timer HashMap.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
     }
 
-    /// Logs and prints the current time of a timer without stopping it.
+    /// Creates a new `Timer` instance with a batch of timers already started.
+    ///
+    /// All timers share a single start instant, which is convenient for pipeline stages that
+    /// begin together. Labels go through the same normalization and bookkeeping as `time`.
     ///
     /// # Arguments
     ///
-    /// * `label` - The label of the timer.
-    /// * `silent` - Whether to suppress printing the message.
+    /// * `labels` - The labels of the timers to start.
     ///
     /// # Returns
     ///
-    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
-    pub fn time_log(&self, label: &str, silent: bool) -> f64 {
+    /// Returns a new `Timer` instance with every label running.
+    pub fn with_started<I, S>(labels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut timer = Self::new();
+        if !ENABLED {
+            return timer;
+        }
+
         #[cfg(not(target_arch = "wasm32"))]
-        if let Some(start_time) = self.timers.get(label) {
-            let duration = start_time.elapsed();
-            let ms = Self::duration_to_ms(duration);
-            if !silent {
-                println!("{}: {:.3}ms", label, ms);
+        {
+            let now = Instant::now();
+            for label in labels {
+                timer.time_at(&label.into(), now);
             }
-            ms
-        } else {
-            eprintln!("Timer '{}' does not exist", label);
-            0.0
         }
-
         #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
-        if let Some(start_time) = self.timers.get(label) {
-            let ms = self.performance.now() - start_time;
-            if !silent {
-                web_sys::console::log_1(&format!("{}: {:.3}ms", label, ms).into());
+        {
+            let now = timer.performance.now();
+            for label in labels {
+                let label = label.into();
+                timer.time(&label);
+                let key = timer.label_key(&label).into_owned();
+                if let Some(start_time) = timer.timers.get_mut(&key) {
+                    *start_time = now;
+                }
             }
-            ms
-        } else {
-            web_sys::console::error_1(&format!("Timer '{}' does not exist", label).into());
-            0.0
         }
-
         #[cfg(all(target_arch = "wasm32", feature = "webworker"))]
-        0.0
+        drop(labels);
+        timer
     }
 
-    /// Ends a timer and prints its runtime.
+    /// Creates a new `Timer` instance that reports every completed timing to a StatsD agent.
+    ///
+    /// Each `time_end` sends a `prefix.label:elapsed|ms` packet over UDP, followed by the
+    /// timer's tags in the Datadog format `|#key:value,key2:value2` if any. Sends are
+    /// non-blocking and their errors are ignored, so reporting never slows the measured code.
+    /// If the socket can't be set up, the error is logged and the timer works without StatsD.
     ///
     /// # Arguments
     ///
-    /// * `label` - The label of the timer.
-    /// * `silent` - Whether to suppress printing the message.
+    /// * `addr` - The address of the StatsD agent, e.g. `127.0.0.1:8125`.
+    /// * `prefix` - The metric name prefix.
     ///
     /// # Returns
     ///
-    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
-    pub fn time_end(&mut self, label: &str, silent: bool) -> f64 {
-        #[cfg(not(target_arch = "wasm32"))]
-        if let Some(start_time) = self.timers.remove(label) {
-            let duration = start_time.elapsed();
-            let ms = Self::duration_to_ms(duration);
-            if !silent {
-                println!("{}: {:.3}ms", label, ms);
+    /// Returns a new `Timer` instance.
+    #[cfg(all(feature = "statsd", not(target_arch = "wasm32")))]
+    pub fn with_statsd(addr: &str, prefix: &str) -> Self {
+        let mut timer = Self::new();
+        match statsd::StatsdClient::connect(addr, prefix) {
+            Ok(client) => timer.statsd = Some(client),
+            Err(err) => eprintln!("Failed to connect to StatsD agent '{}': {}", addr, err),
+        }
+        timer
+    }
+
+    /// Creates a new `Timer` instance from bytes produced by `to_bytes`.
+    ///
+    /// The new timer starts with the encoded statistics, retained samples and counters and no
+    /// running timers.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The encoded timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded `Timer`, or an `InvalidData` error if `bytes` is not a valid encoding.
+    #[cfg(feature = "binary")]
+    pub fn from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
+        let mut timer = Self::new();
+        timer.load_binary(bytes)?;
+        Ok(timer)
+    }
+
+    /// Estimates the resolution of the clock timers are measured with.
+    ///
+    /// `Instant::now()` is sampled in a tight loop and the smallest non-zero difference between
+    /// consecutive samples is taken. Measurements near this value can't be trusted, so it's a
+    /// good starting point for `set_resolution_floor`. The estimate takes well under a
+    /// millisecond on common platforms.
+    ///
+    /// # Returns
+    ///
+    /// Returns the smallest observed clock step, or `Duration::MAX` if the clock never advanced
+    /// during sampling.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn clock_resolution() -> Duration {
+        const STEPS: usize = 100;
+        const MAX_SAMPLES: usize = 10_000_000;
+
+        let mut best = Duration::MAX;
+        let mut steps = 0;
+        let mut last = Instant::now();
+        for _ in 0..MAX_SAMPLES {
+            let now = Instant::now();
+            let delta = now.saturating_duration_since(last);
+            if !delta.is_zero() {
+                best = best.min(delta);
+                steps += 1;
+                if steps == STEPS {
+                    break;
+                }
             }
-            ms
-        } else {
-            eprintln!("Timer '{}' does not exist", label);
-            0.0
+            last = now;
         }
+        best
+    }
 
-        #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
-        if let Some(start_time) = self.timers.remove(label) {
-            let ms = self.performance.now() - start_time;
-            if !silent {
-                web_sys::console::log_1(&format!("{}: {:.3}ms", label, ms).into());
+    /// Formats a duration as a zero-padded clock string `HH:MM:SS.mmm`.
+    ///
+    /// Hours are not wrapped at 24, so very long durations print more than two hour digits.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - The duration to format.
+    ///
+    /// # Returns
+    ///
+    /// Returns the formatted duration, e.g. `00:01:23.456` for 83.456 seconds.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn format_clock(duration: Duration) -> String {
+        let total_ms = duration.as_millis();
+        let hours = total_ms / 3_600_000;
+        let minutes = total_ms / 60_000 % 60;
+        let seconds = total_ms / 1000 % 60;
+        let millis = total_ms % 1000;
+        format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, seconds, millis)
+    }
+
+    /// A built-in label normalizer that trims surrounding whitespace and lowercases the label.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label to normalize.
+    ///
+    /// # Returns
+    ///
+    /// Returns the normalized label.
+    pub fn trim_lowercase(label: &str) -> String {
+        label.trim().to_lowercase()
+    }
+
+    /// A built-in label normalizer that also unifies separators.
+    ///
+    /// The label is trimmed and lowercased, and every run of whitespace, `-` or `_` becomes a
+    /// single `_`, so `db-query`, `db_query` and `DB Query` all map to `db_query`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label to normalize.
+    ///
+    /// # Returns
+    ///
+    /// Returns the normalized label.
+    pub fn unify_separators(label: &str) -> String {
+        let mut out = String::with_capacity(label.len());
+        for (i, word) in label
+            .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+            .filter(|word| !word.is_empty())
+            .enumerate()
+        {
+            if i > 0 {
+                out.push('_');
             }
-            ms
-        } else {
-            web_sys::console::error_1(&format!("Timer '{}' does not exist", label).into());
-            0.0
+            out.push_str(&word.to_lowercase());
         }
+        out
+    }
 
-        #[cfg(all(target_arch = "wasm32", feature = "webworker"))]
-        0.0
+    /// Starts a self-contained measurement that doesn't touch any timer map.
+    ///
+    /// The returned `Measurement` captures its own start; `stop` turns it into a
+    /// `CompletedMeasurement` to keep in your own collections. This avoids map bookkeeping
+    /// and locking for one-off timings.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the measurement.
+    ///
+    /// # Returns
+    ///
+    /// Returns the running `Measurement`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_measurement(label: &str) -> Measurement {
+        Measurement::start(label)
     }
 
     /// Returns a global singleton instance of Timer
@@ -200,7 +628,7 @@ impl Timer {
             ONCE.call_once(|| {
                 SINGLETON = Some(self::Timer::new());
             });
-            SINGLETON.as_mut().unwrap()
+            (*std::ptr::addr_of_mut!(SINGLETON)).as_mut().unwrap()
         }
     }
 
@@ -213,68 +641,5799 @@ impl Timer {
     /// # Returns
     ///
     /// Returns the converted milliseconds as a floating-point number.
-    #[cfg(not(target_arch = "wasm32"))]
     fn duration_to_ms(duration: Duration) -> f64 {
         (duration.as_secs() as f64) * 1000.0 + (duration.subsec_nanos() as f64) / 1_000_000.0
     }
 }
 
-/// Implements the `Default` trait for `Timer`.
-impl Default for Timer {
-    /// Creates a default `Timer` instance.
+impl<S: BuildHasher> Timer<S> {
+    /// Creates a new `Timer` instance whose timer HashMap uses the given hasher.
+    ///
+    /// The default SipHash resists adversarial keys, which timer labels rarely are; a faster
+    /// hasher such as FxHash speeds up hot instrumentation with many short-lived labels.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - The hasher builder for the timer HashMap.
     ///
     /// # Returns
     ///
-    /// Returns a new `Timer` instance.
-    fn default() -> Self {
-        Self::new()
+    /// Returns a new `Timer` instance with an empty timer HashMap.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
     }
-}
 
-/// Test module
-#[cfg(test)]
-mod tests {
-    use super::*;
-    #[cfg(not(target_arch = "wasm32"))]
-    use std::thread::sleep;
-    #[cfg(not(target_arch = "wasm32"))]
-    use std::time::Duration;
+    /// Creates a new `Timer` instance with room for at least `capacity` timers, whose timer
+    /// HashMap uses the given hasher.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of concurrent timers to reserve space for.
+    /// * `hasher` - The hasher builder for the timer HashMap.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `Timer` instance with an empty, pre-allocated timer HashMap.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if ENABLED {
+            PROCESS_START.get_or_init(Instant::now);
+        }
 
-    /// Tests Timer::new() and Timer::default()
-    #[test]
-    fn test_timer_new() {
-        let timer = Timer::default();
-        assert!(timer.timers.is_empty());
+        Timer {
+            #[cfg(not(target_arch = "wasm32"))]
+            timers: HashMap::with_capacity_and_hasher(capacity, hasher),
+            #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+            timers: HashMap::with_capacity_and_hasher(capacity, hasher),
+            #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+            performance: window().unwrap().performance().unwrap(),
+            scale: 1.0,
+            #[cfg(not(target_arch = "wasm32"))]
+            resolution_floor: Duration::ZERO,
+            #[cfg(not(target_arch = "wasm32"))]
+            epoch: None,
+            track_usage: false,
+            unmatched_ends: Vec::new(),
+            display_mapper: None,
+            label_normalizer: None,
+            max_label_len: None,
+            last_logged: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            last_end: None,
+            peak_concurrent: 0,
+            auto_labels: 0,
+            frame: None,
+            frames: 0,
+            reentrant: false,
+            depths: HashMap::new(),
+            sample_counters: HashMap::new(),
+            stats: HashMap::new(),
+            retain_samples: false,
+            samples: HashMap::new(),
+            #[cfg(feature = "approx-quantiles")]
+            quantiles: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            paused: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            paused_total: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            show_idle: false,
+            parents: HashMap::new(),
+            scopes: Vec::new(),
+            locations: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            wall_starts: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            track_skew: false,
+            tags: HashMap::new(),
+            #[cfg(all(feature = "prometheus", not(target_arch = "wasm32")))]
+            exemplars: HashMap::new(),
+            categories: HashMap::new(),
+            category_filter: None,
+            budgets: HashMap::new(),
+            baselines: HashMap::new(),
+            weights: HashMap::new(),
+            best: HashMap::new(),
+            #[cfg(feature = "num-format")]
+            thousands_separator: None,
+            #[cfg(feature = "num-format")]
+            decimal_separator: '.',
+            thresholds: None,
+            counters: RwLock::new(HashMap::new()),
+            #[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+            alloc_starts: HashMap::new(),
+            #[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+            allocations: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            missing_warning: MissingTimerWarning::Always,
+            #[cfg(not(target_arch = "wasm32"))]
+            warned: Mutex::new(HashMap::new()),
+            #[cfg(not(target_arch = "wasm32"))]
+            output: Mutex::new(output::Output::default()),
+            #[cfg(not(target_arch = "wasm32"))]
+            channel: None,
+            #[cfg(all(feature = "statsd", not(target_arch = "wasm32")))]
+            statsd: None,
+            #[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+            ndjson_sink: None,
+            #[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+            trace: None,
+        }
     }
 
-    /// Tests Timer::time() method
-    #[test]
-    fn test_timer_time() {
-        let mut timer = Timer::new();
-        timer.time("test");
-        assert!(timer.timers.contains_key("test"));
+    /// Starts a new timer.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    pub fn time(&mut self, label: &str) {
+        if !ENABLED {
+            return;
+        }
+        let normalized = self.normalize_label(label);
+        if let Some((max_len, policy)) = self.max_label_len {
+            if normalized.chars().count() > max_len {
+                match policy {
+                    LabelLengthPolicy::Truncate => eprintln!(
+                        "Timer label '{}' exceeds {} characters and was truncated",
+                        label, max_len
+                    ),
+                    LabelLengthPolicy::Reject => {
+                        eprintln!(
+                            "Timer label '{}' exceeds {} characters and was rejected",
+                            label, max_len
+                        );
+                        return;
+                    }
+                }
+            }
+        }
+        let key = self.truncate_label(normalized);
+        let label = key.as_ref();
+        if self.reentrant && self.timers.contains_key(label) {
+            *self.depths.entry(label.to_string()).or_default() += 1;
+            return;
+        }
+        self.categories.remove(label);
+        self.last_logged.remove(label);
+        self.tags.remove(label);
+        self.locations.remove(label);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.paused.remove(label);
+            self.paused_total.remove(label);
+            if self.track_skew {
+                self.wall_starts
+                    .insert(label.to_string(), SystemTime::now());
+            }
+            self.timers.insert(label.to_string(), Instant::now());
+            self.peak_concurrent = self.peak_concurrent.max(self.timers.len());
+        }
+
+        // Snapshot the allocation count last so the bookkeeping above is not counted.
+        #[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+        {
+            self.alloc_starts.insert(label.to_string(), 0);
+            match (alloc_count::allocations(), self.alloc_starts.get_mut(label)) {
+                (Some(count), Some(start)) => *start = count,
+                _ => {
+                    self.alloc_starts.remove(label);
+                }
+            }
+        }
+
+        #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+        {
+            self.timers
+                .insert(label.to_string(), self.performance.now());
+            self.peak_concurrent = self.peak_concurrent.max(self.timers.len());
+        }
     }
 
-    /// Tests Timer::time_log() method
-    #[test]
-    #[cfg(not(target_arch = "wasm32"))]
-    fn test_timer_time_log() {
-        let mut timer = Timer::new();
-        timer.time("test_time_log");
-        sleep(Duration::from_millis(10));
-        let ms = timer.time_log("test_time_log", false);
-        assert!(ms > 10.0 && ms < 15.0);
+    /// Starts a new timer and records the caller's source location.
+    ///
+    /// When the timer ends, its call site is printed after the label, e.g.
+    /// `parse (src/main.rs:42): 12.000ms`, which tells apart uses of the same label. The
+    /// `time_here!` macro is the usual way to call this.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    #[track_caller]
+    pub fn time_here(&mut self, label: &str) {
+        let location = Location::caller();
+        self.time(label);
+        let key = self.label_key(label).into_owned();
+        if self.timers.contains_key(&key) {
+            self.locations.insert(key, location);
+        }
     }
 
-    /// Tests Timer::time_end() method
-    #[test]
+    /// Starts a new timer from a caller-provided start instant.
+    ///
+    /// This lets an `Instant` captured earlier, e.g. at the very top of a handler, be registered
+    /// once the timer is reachable, so `time_end` measures from that point. The instant must
+    /// come from `Instant::now()`, the same monotonic clock the timer uses. With the
+    /// `alloc-count` feature, allocations are still only counted from this call on.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    /// * `start` - The instant the timer started at.
     #[cfg(not(target_arch = "wasm32"))]
-    fn test_timer_time_end() {
+    pub fn time_at(&mut self, label: &str, start: Instant) {
+        if !ENABLED {
+            return;
+        }
+        let key = self.label_key(label).into_owned();
+        let nested = self.reentrant && self.timers.contains_key(&key);
+        self.time(label);
+        if nested {
+            return;
+        }
+        if let Some(start_time) = self.timers.get_mut(&key) {
+            *start_time = start;
+            let age = Instant::now().saturating_duration_since(start);
+            let wall_start = SystemTime::now().checked_sub(age);
+            if let (Some(wall_start), Some(slot)) = (wall_start, self.wall_starts.get_mut(&key)) {
+                *slot = wall_start;
+            }
+        }
+    }
+
+    /// Starts a new timer under an automatically generated label.
+    ///
+    /// Labels are `timer_0`, `timer_1`, ... counting per `Timer`, which saves inventing names
+    /// during ad-hoc exploration.
+    ///
+    /// # Returns
+    ///
+    /// Returns the generated label, to pass to `time_end` later.
+    pub fn time_auto(&mut self) -> String {
+        let label = format!("timer_{}", self.auto_labels);
+        self.auto_labels += 1;
+        self.time(&label);
+        label
+    }
+
+    /// Starts a new timer only if a condition holds.
+    ///
+    /// This supports head-based sampling, e.g. timing only requests flagged for tracing,
+    /// without an `if` around every call site.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    /// * `cond` - Whether to start the timer.
+    pub fn time_if(&mut self, label: &str, cond: bool) {
+        if cond {
+            self.time(label);
+        }
+    }
+
+    /// Starts a new timer in a category.
+    ///
+    /// Categories give verbosity control: with a category filter set, only timers in allowed
+    /// categories print. Timers in other categories are still measured and recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    /// * `category` - The category of the timer, e.g. `request` or `lock_acquire`.
+    pub fn time_cat(&mut self, label: &str, category: &str) {
+        self.time(label);
+        let key = self.label_key(label).into_owned();
+        self.categories.insert(key, category.to_string());
+    }
+
+    /// Restricts printing to timers in the given categories.
+    ///
+    /// Timers started without a category are unaffected by the filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `allowed` - The categories whose timers print.
+    pub fn set_category_filter(&mut self, allowed: &[&str]) {
+        self.category_filter = Some(allowed.iter().map(|c| c.to_string()).collect());
+    }
+
+    /// Removes the category filter, so timers in every category print.
+    pub fn clear_category_filter(&mut self) {
+        self.category_filter = None;
+    }
+
+    /// Returns whether output for a timer should be printed.
+    fn should_print(&self, label: &str, silent: bool) -> bool {
+        if silent {
+            return false;
+        }
+        match (&self.category_filter, self.categories.get(label)) {
+            (Some(allowed), Some(category)) => allowed.contains(category),
+            _ => true,
+        }
+    }
+
+    /// Starts a batch of timers in one call.
+    ///
+    /// # Arguments
+    ///
+    /// * `labels` - The labels of the timers to start.
+    pub fn time_many(&mut self, labels: &[&str]) {
+        for label in labels {
+            self.time(label);
+        }
+    }
+
+    /// Starts a timer on every `n`th call for the given label.
+    ///
+    /// A call counter is kept per label, so only the `n`th, `2n`th, ... calls actually start the
+    /// timer. This keeps the overhead negligible for operations that run millions of times.
+    /// Values of `n` below 1 are treated as 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    /// * `n` - The sampling interval.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if this call was sampled and the timer was started.
+    pub fn time_every_n(&mut self, label: &str, n: u64) -> bool {
+        if !ENABLED {
+            return false;
+        }
+        let key = self.label_key(label).into_owned();
+        let counter = self.sample_counters.entry(key).or_insert(0);
+        *counter += 1;
+        let sampled = counter.is_multiple_of(n.max(1));
+        if sampled {
+            self.time(label);
+        }
+        sampled
+    }
+
+    /// Renames a running timer without losing its start instant.
+    ///
+    /// This is useful when a timer has to be started before its final label is known.
+    /// The pause state and category move along with the timer.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The current label of the timer.
+    /// * `to` - The new label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the timer was renamed, or `false` if `from` doesn't exist or a timer
+    /// labelled `to` is already running.
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        let from = self.label_key(from).into_owned();
+        let to = self.label_key(to).into_owned();
+        if from == to {
+            return self.timers.contains_key(&from);
+        }
+        if self.timers.contains_key(&to) {
+            return false;
+        }
+        let Some(start_time) = self.timers.remove(&from) else {
+            return false;
+        };
+        self.timers.insert(to.clone(), start_time);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(paused_at) = self.paused.remove(&from) {
+            self.paused.insert(to.clone(), paused_at);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(paused_for) = self.paused_total.remove(&from) {
+            self.paused_total.insert(to.clone(), paused_for);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(wall_start) = self.wall_starts.remove(&from) {
+            self.wall_starts.insert(to.clone(), wall_start);
+        }
+        #[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+        if let Some(allocs) = self.alloc_starts.remove(&from) {
+            self.alloc_starts.insert(to.clone(), allocs);
+        }
+        if let Some(logged) = self.last_logged.remove(&from) {
+            self.last_logged.insert(to.clone(), logged);
+        }
+        if let Some(location) = self.locations.remove(&from) {
+            self.locations.insert(to.clone(), location);
+        }
+        if let Some(tags) = self.tags.remove(&from) {
+            self.tags.insert(to.clone(), tags);
+        }
+        if let Some(depth) = self.depths.remove(&from) {
+            self.depths.insert(to.clone(), depth);
+        }
+        if let Some(category) = self.categories.remove(&from) {
+            self.categories.insert(to, category);
+        }
+        true
+    }
+
+    /// Logs and prints the current time of a timer without stopping it.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    pub fn time_log(&self, label: &str, silent: bool) -> f64 {
+        if !ENABLED {
+            return 0.0;
+        }
+        let key = self.label_key(label);
+        let label = key.as_ref();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(&start_time) = self.timers.get(label) {
+            let duration = self.scaled(self.running_time(label, start_time, Instant::now()));
+            let ms = Timer::duration_to_ms(duration);
+            if self.should_print(label, silent) {
+                self.emit(
+                    label,
+                    format!("{}: {}ms", self.display_label(label), self.format_ms(ms)),
+                );
+            }
+            ms
+        } else {
+            self.warn_missing(label);
+            0.0
+        }
+
+        #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+        if let Some(start_time) = self.timers.get(label) {
+            let ms = (self.performance.now() - start_time).max(0.0) * self.scale;
+            if self.should_print(label, silent) {
+                web_sys::console::log_1(
+                    &format!("{}: {}ms", self.display_label(label), self.format_ms(ms)).into(),
+                );
+            }
+            ms
+        } else {
+            web_sys::console::error_1(&format!("Timer '{}' does not exist", label).into());
+            0.0
+        }
+
+        #[cfg(all(target_arch = "wasm32", feature = "webworker"))]
+        0.0
+    }
+
+    /// Logs and prints the current time of a timer along with the increment since its last log.
+    ///
+    /// The message is printed as `label: total=12.345ms (+2.345ms)`. The first call measures the
+    /// increment from the timer's start. Paused time is excluded from both values.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns `(total_elapsed_ms, delta_since_last_log_ms)`, or `(0.0, 0.0)` if the timer
+    /// doesn't exist.
+    pub fn time_log_delta(&mut self, label: &str) -> (f64, f64) {
+        if !ENABLED {
+            return (0.0, 0.0);
+        }
+        let key = self.label_key(label).into_owned();
+        let Some(total) = self.running_ms(&key) else {
+            #[cfg(not(target_arch = "wasm32"))]
+            self.warn_missing(&key);
+            #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+            web_sys::console::error_1(&format!("Timer '{}' does not exist", key).into());
+            return (0.0, 0.0);
+        };
+        let delta = total - self.last_logged.insert(key.clone(), total).unwrap_or(0.0);
+        if self.should_print(&key, false) {
+            let message = format!(
+                "{}: total={}ms (+{}ms)",
+                self.display_label(&key),
+                self.format_ms(total),
+                self.format_ms(delta)
+            );
+            #[cfg(not(target_arch = "wasm32"))]
+            self.emit(&key, message);
+            #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+            web_sys::console::log_1(&message.into());
+        }
+        (total, delta)
+    }
+
+    /// Logs and prints the current time of a timer with a lazily built message.
+    ///
+    /// The message is printed as `label: 12.345ms message`. The closure is only called when
+    /// the line is actually printed, so no formatting is wasted when output is silenced or
+    /// the timer's category is filtered out.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    /// * `msg` - A closure producing the message to print after the elapsed time.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    pub fn time_log_with(&self, label: &str, silent: bool, msg: impl FnOnce() -> String) -> f64 {
+        if !ENABLED {
+            return 0.0;
+        }
+        let key = self.label_key(label);
+        let label = key.as_ref();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(&start_time) = self.timers.get(label) {
+            let duration = self.scaled(self.running_time(label, start_time, Instant::now()));
+            let ms = Timer::duration_to_ms(duration);
+            if self.should_print(label, silent) {
+                self.emit(
+                    label,
+                    format!(
+                        "{}: {}ms {}",
+                        self.display_label(label),
+                        self.format_ms(ms),
+                        msg()
+                    ),
+                );
+            }
+            ms
+        } else {
+            self.warn_missing(label);
+            0.0
+        }
+
+        #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+        if let Some(start_time) = self.timers.get(label) {
+            let ms = (self.performance.now() - start_time).max(0.0) * self.scale;
+            if self.should_print(label, silent) {
+                web_sys::console::log_1(
+                    &format!(
+                        "{}: {}ms {}",
+                        self.display_label(label),
+                        self.format_ms(ms),
+                        msg()
+                    )
+                    .into(),
+                );
+            }
+            ms
+        } else {
+            web_sys::console::error_1(&format!("Timer '{}' does not exist", label).into());
+            0.0
+        }
+
+        #[cfg(all(target_arch = "wasm32", feature = "webworker"))]
+        0.0
+    }
+
+    /// Like `time_log`, but returns the elapsed time as `Millis` to make the unit explicit.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the time the timer has been running, or zero if the timer doesn't exist.
+    pub fn time_log_typed(&self, label: &str, silent: bool) -> Millis {
+        Millis(self.time_log(label, silent))
+    }
+
+    /// Logs and prints an instantaneous event relative to a running timer's start.
+    ///
+    /// The message is printed as `label +12.345ms: name`, using the timer's start as the zero point.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer to anchor the event to.
+    /// * `name` - The name of the event.
+    ///
+    /// # Returns
+    ///
+    /// Returns the event's offset from the timer's start in milliseconds, or 0.0 if the timer doesn't exist.
+    pub fn event(&self, label: &str, name: &str) -> f64 {
+        if !ENABLED {
+            return 0.0;
+        }
+        let key = self.label_key(label);
+        let label = key.as_ref();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(&start_time) = self.timers.get(label) {
+            let duration = self.scaled(self.running_time(label, start_time, Instant::now()));
+            let ms = Timer::duration_to_ms(duration);
+            if self.should_print(label, false) {
+                self.emit(
+                    label,
+                    format!(
+                        "{} +{}ms: {}",
+                        self.display_label(label),
+                        self.format_ms(ms),
+                        name
+                    ),
+                );
+            }
+            ms
+        } else {
+            self.warn_missing(label);
+            0.0
+        }
+
+        #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+        if let Some(start_time) = self.timers.get(label) {
+            let ms = (self.performance.now() - start_time).max(0.0) * self.scale;
+            if self.should_print(label, false) {
+                web_sys::console::log_1(
+                    &format!(
+                        "{} +{}ms: {}",
+                        self.display_label(label),
+                        self.format_ms(ms),
+                        name
+                    )
+                    .into(),
+                );
+            }
+            ms
+        } else {
+            web_sys::console::error_1(&format!("Timer '{}' does not exist", label).into());
+            0.0
+        }
+
+        #[cfg(all(target_arch = "wasm32", feature = "webworker"))]
+        0.0
+    }
+
+    /// Makes starting a running timer nest instead of restarting it.
+    ///
+    /// This times recursive functions under one label: in reentrant mode, `time` on a running
+    /// label only increments its nesting depth, and each inner `time_end` decrements it again
+    /// without stopping the clock and returns 0.0. Only the outermost `time_end` stops the
+    /// timer and reports the total time. Disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether timers are reentrant.
+    pub fn set_reentrant(&mut self, enabled: bool) {
+        self.reentrant = enabled;
+        if !enabled {
+            self.depths.clear();
+        }
+    }
+
+    /// Leaves one nesting level of a reentrant timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the timer was nested, so it must keep running.
+    fn leave_nested(&mut self, label: &str) -> bool {
+        let Some(depth) = self.depths.get_mut(label) else {
+            return false;
+        };
+        *depth -= 1;
+        if *depth == 0 {
+            self.depths.remove(label);
+        }
+        true
+    }
+
+    /// Ends a timer and prints its runtime.
+    ///
+    /// Ending a paused timer reports only the running time accumulated before the pause; the
+    /// paused interval up to the end is never counted.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    pub fn time_end(&mut self, label: &str, silent: bool) -> f64 {
+        if !ENABLED {
+            return 0.0;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.time_end_at(label, Instant::now(), silent);
+
+        #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+        {
+            let key = self.label_key(label);
+            let label = key.as_ref();
+            if self.leave_nested(label) {
+                return 0.0;
+            }
+            if let Some(start_time) = self.timers.remove(label) {
+                self.last_logged.remove(label);
+                self.tags.remove(label);
+                let ms = (self.performance.now() - start_time).max(0.0) * self.scale;
+                if self.should_print(label, silent) {
+                    web_sys::console::log_1(&self.end_message(label, ms).into());
+                }
+                self.locations.remove(label);
+                self.record(label, Duration::from_secs_f64(ms / 1000.0));
+                ms
+            } else {
+                web_sys::console::error_1(&format!("Timer '{}' does not exist", label).into());
+                if self.track_usage {
+                    self.unmatched_ends.push(label.to_string());
+                }
+                0.0
+            }
+        }
+
+        #[cfg(all(target_arch = "wasm32", feature = "webworker"))]
+        0.0
+    }
+
+    /// Ends a timer and prints its runtime as a logfmt line.
+    ///
+    /// The line looks like `label=parse duration_ms=12.300 msg="cache miss" trace_id=abc`: the
+    /// message and the timer's tags follow as extra pairs when present, and values containing
+    /// spaces, `=` or quotes are quoted. The duration always uses the plain `12.300` format so
+    /// log aggregators can parse it.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `msg` - An optional message to include as the `msg` key.
+    /// * `silent` - Whether to suppress printing the line.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    pub fn time_end_logfmt(&mut self, label: &str, msg: Option<&str>, silent: bool) -> f64 {
+        let key = self.label_key(label).into_owned();
+        if !ENABLED || !self.timers.contains_key(&key) || self.depths.contains_key(&key) {
+            return self.time_end(label, silent);
+        }
+        let tags = self.tags.get(&key).cloned().unwrap_or_default();
+        let ms = self.time_end(label, true);
+        if self.should_print(&key, silent) {
+            let line = self.logfmt_line(&key, ms, msg, &tags);
+            #[cfg(not(target_arch = "wasm32"))]
+            self.emit(&key, line);
+            #[cfg(target_arch = "wasm32")]
+            web_sys::console::log_1(&line.into());
+        }
+        ms
+    }
+
+    /// Formats a completed measurement as a logfmt line.
+    fn logfmt_line(
+        &self,
+        label: &str,
+        ms: f64,
+        msg: Option<&str>,
+        tags: &[(String, String)],
+    ) -> String {
+        let mut line = format!(
+            "label={} duration_ms={:.3}",
+            logfmt_value(&self.display_label(label)),
+            ms
+        );
+        if let Some(msg) = msg {
+            line.push_str(&format!(" msg={}", logfmt_value(msg)));
+        }
+        for (key, value) in tags {
+            line.push_str(&format!(" {}={}", key, logfmt_value(value)));
+        }
+        line
+    }
+
+    /// Sets whether started timers also capture the wall-clock time, for `time_end_with_skew`.
+    ///
+    /// This is off by default, which keeps the system-time read off the hot path of `time`.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to capture wall-clock start times.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_track_skew(&mut self, enabled: bool) {
+        self.track_skew = enabled;
+        if !enabled {
+            self.wall_starts.clear();
+        }
+    }
+
+    /// Ends a timer and measures its elapsed time on both the monotonic and the wall clock.
+    ///
+    /// The monotonic clock behind `time_end` keeps ticking steadily, while the wall clock
+    /// follows the system time. The two numbers normally agree to within a millisecond. A wall
+    /// time well above the monotonic time usually means the process was suspended, e.g. by
+    /// laptop sleep, on platforms whose monotonic clock stops during suspend; a large
+    /// difference in either direction can also mean the system clock was adjusted, e.g. by NTP.
+    /// Either way, the measurement is suspect. Pausing and the scale factor only apply to the
+    /// monotonic time, so compare the two for plain timers.
+    ///
+    /// The wall clock is only read for timers started while `set_track_skew(true)` is on;
+    /// for other timers both numbers are the monotonic time.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns `(monotonic_ms, wall_ms)`, or `(0.0, 0.0)` if the timer doesn't exist. If the
+    /// wall clock jumped back past the start, `wall_ms` is negative.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn time_end_with_skew(&mut self, label: &str) -> (f64, f64) {
+        let key = self.label_key(label).into_owned();
+        let wall_start = self.wall_starts.get(&key).copied();
+        let monotonic_ms = self.time_end(label, false);
+        let wall_ms = match wall_start {
+            Some(wall_start) => match SystemTime::now().duration_since(wall_start) {
+                Ok(elapsed) => Timer::duration_to_ms(elapsed),
+                Err(err) => -Timer::duration_to_ms(err.duration()),
+            },
+            None => monotonic_ms,
+        };
+        (monotonic_ms, wall_ms)
+    }
+
+    /// Ends a timer, prints its runtime and returns the label's updated statistics.
+    ///
+    /// This avoids a second lookup when the aggregate is needed right away, e.g. to check
+    /// whether this run was the slowest so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer
+    /// doesn't exist, together with a copy of the label's statistics after the update.
+    pub fn time_end_with_stats(&mut self, label: &str) -> (f64, TimerStats) {
+        let ms = self.time_end(label, false);
+        let stats = self.stats(label).copied().unwrap_or_default();
+        (ms, stats)
+    }
+
+    /// Like `time_end`, but returns the elapsed time as `Millis` to make the unit explicit.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the time the timer has been running, or zero if the timer doesn't exist.
+    pub fn time_end_typed(&mut self, label: &str, silent: bool) -> Millis {
+        Millis(self.time_end(label, silent))
+    }
+
+    /// Ends a timer and prints its runtime only if a condition holds.
+    ///
+    /// When `cond` is false, nothing is recorded or printed and a missing timer is not reported,
+    /// so it pairs with `time_if` using the same condition.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `cond` - Whether to end the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if `cond` is false
+    /// or the timer doesn't exist.
+    pub fn time_end_if(&mut self, label: &str, cond: bool) -> f64 {
+        if !cond {
+            return 0.0;
+        }
+        self.time_end(label, false)
+    }
+
+    /// Ends a timer at an explicit instant and prints its runtime.
+    ///
+    /// This is useful for replaying recorded `(start, end)` pairs deterministically.
+    /// If `end` is earlier than the timer's start, the elapsed time is clamped to zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `end` - The instant at which the timer ended.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds between the timer's start and `end`, or 0.0 if the timer doesn't exist.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn time_end_at(&mut self, label: &str, end: Instant, silent: bool) -> f64 {
+        if !ENABLED {
+            return 0.0;
+        }
+        let key = self.label_key(label);
+        let label = key.as_ref();
+        if self.leave_nested(label) {
+            return 0.0;
+        }
+
+        #[cfg(feature = "alloc-count")]
+        let allocs = alloc_count::allocations().and_then(|count| {
+            let start = self.alloc_starts.remove(label)?;
+            Some(count.saturating_sub(start))
+        });
+
+        if let Some(start_time) = self.timers.remove(label) {
+            let running = self.running_time(label, start_time, end);
+            let idle = self.idle_ratio(label, running, end);
+            let duration = self.scaled(running);
+            self.paused.remove(label);
+            self.paused_total.remove(label);
+            self.wall_starts.remove(label);
+            self.last_logged.remove(label);
+            self.last_end = Some(end);
+            let ms = Timer::duration_to_ms(duration);
+            #[cfg(feature = "prometheus")]
+            self.record_exemplar(label, ms);
+            if self.should_print(label, silent) {
+                let offset = self
+                    .epoch
+                    .map(|epoch| Timer::duration_to_ms(end.saturating_duration_since(epoch)));
+                let message = match idle {
+                    Some(ratio) if self.show_idle => format!(
+                        "{} ({:.0}% idle)",
+                        self.end_message_at(label, ms, offset),
+                        ratio * 100.0
+                    ),
+                    _ => self.end_message_at(label, ms, offset),
+                };
+                #[cfg(feature = "alloc-count")]
+                let message = match allocs {
+                    Some(allocs) => format!("{}, {} allocs", message, allocs),
+                    None => format!("{}, n/a allocs", message),
+                };
+                #[cfg(feature = "log")]
+                if let Some(severity) = self.severity(ms) {
+                    log::log!(target: "timelog", severity.log_level(), "{}", message);
+                }
+                self.emit(label, message);
+            }
+            self.locations.remove(label);
+            #[cfg(feature = "alloc-count")]
+            match allocs {
+                Some(allocs) => {
+                    self.allocations.insert(label.to_string(), allocs);
+                }
+                None => {
+                    self.allocations.remove(label);
+                }
+            }
+            self.record(label, duration);
+            if let Some(tx) = &self.channel {
+                if tx.send((label.to_string(), duration)).is_err() {
+                    eprintln!("Timer channel receiver was dropped, detaching channel");
+                    self.channel = None;
+                }
+            }
+            #[cfg(feature = "statsd")]
+            if let Some(client) = &self.statsd {
+                client.send_timing(label, ms, self.tags.get(label).map_or(&[], Vec::as_slice));
+            }
+            self.tags.remove(label);
+            #[cfg(feature = "json")]
+            self.write_ndjson(label, ms);
+            #[cfg(feature = "json")]
+            if let Some(trace) = self.trace.as_mut() {
+                let origin = *PROCESS_START.get_or_init(Instant::now);
+                trace.push((
+                    label.to_string(),
+                    start_time.saturating_duration_since(origin).as_micros(),
+                    duration.as_micros(),
+                    trace_thread_id(),
+                ));
+            }
+            ms
+        } else {
+            self.warn_missing(label);
+            if self.track_usage {
+                self.unmatched_ends.push(label.to_string());
+            }
+            0.0
+        }
+    }
+
+    /// Ends a timer without printing and returns its runtime as a clock string.
+    ///
+    /// Milliseconds are hard to read for operations lasting minutes or hours, so the runtime
+    /// is formatted by `format_clock`, e.g. `00:01:23.456`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the runtime as `HH:MM:SS.mmm`, or `00:00:00.000` if the timer doesn't exist.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn time_end_clock(&mut self, label: &str) -> String {
+        let ms = self.time_end(label, true);
+        Timer::format_clock(Duration::from_secs_f64(ms.max(0.0) / 1000.0))
+    }
+
+    /// Returns the number of allocations made during a label's last completed measurement.
+    ///
+    /// Requires a `CountingAllocator` to be installed as the global allocator.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the allocation count, or `None` if the label hasn't been ended or no counting
+    /// allocator is installed.
+    #[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+    pub fn allocations(&self, label: &str) -> Option<u64> {
+        self.allocations
+            .get(self.label_key(label).as_ref())
+            .copied()
+    }
+
+    /// Starts a timer that ends automatically when the returned guard is dropped.
+    ///
+    /// Scopes opened while another scope guard of the same timer is alive are recorded as
+    /// its children, so nested guards build the parent-child tree without explicit parents:
+    ///
+    /// ```
+    /// use timelog::Timer;
+    ///
+    /// let mut timer = Timer::new();
+    /// {
+    ///     let mut parent = timer.scope("request");
+    ///     let _child = parent.scope("parse");
+    /// }
+    /// assert_eq!(timer.parent("parse"), Some("request"));
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `ScopeGuard` that ends the timer on drop.
+    pub fn scope(&mut self, label: &str) -> ScopeGuard<'_, S> {
+        let parent = self.scopes.last().cloned();
+        let index = self.enter_scope(label);
+        if let (Some(index), Some(parent)) = (index, parent) {
+            let key = &self.scopes[index];
+            if *key != parent {
+                self.parents.insert(key.clone(), parent);
+            }
+        }
+        ScopeGuard::new(self, label, index)
+    }
+
+    /// Starts a timer for a scope guard and pushes its key onto the scope stack.
+    ///
+    /// Returns the position of the key on the stack, or `None` if the timer wasn't started,
+    /// e.g. because its label was rejected. With the `disabled` feature no timer starts, but
+    /// scopes are still tracked so parents are recorded.
+    fn enter_scope(&mut self, label: &str) -> Option<usize> {
+        self.time(label);
+        let key = self.label_key(label).into_owned();
+        if ENABLED && !self.timers.contains_key(&key) {
+            return None;
+        }
+        self.scopes.push(key);
+        Some(self.scopes.len() - 1)
+    }
+
+    /// Pops a scope guard's key, and any key above it, off the scope stack.
+    pub(crate) fn leave_scope(&mut self, index: usize) {
+        self.scopes.truncate(index);
+    }
+
+    /// Times a closure under a label and returns its result.
+    ///
+    /// The closure receives the timer, so it can measure nested steps. Like `scope`, measures
+    /// opened inside another measure or scope of the same timer are recorded as its children,
+    /// which builds the tree shown by `print_tree` without declaring parents.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    /// * `f` - The closure to time.
+    ///
+    /// # Returns
+    ///
+    /// Returns the closure's result.
+    pub fn measure<R>(&mut self, label: &str, f: impl FnOnce(&mut Self) -> R) -> R {
+        let mut guard = self.scope(label);
+        f(&mut guard)
+    }
+
+    /// Builds a report of the accumulated statistics as a parent-child tree.
+    ///
+    /// Each label is listed under its parent as recorded by `scope` and `measure`, indented by
+    /// two spaces per level, e.g. `  parse: 12.000ms (3 calls)`. Labels whose parent has no
+    /// statistics are listed at the top level. Siblings are sorted by label.
+    ///
+    /// Every parent ends with a synthetic `<self>` node holding the time spent in the parent
+    /// outside any instrumented child: its total minus the totals of its children, clamped at
+    /// zero.
+    ///
+    /// # Returns
+    ///
+    /// Returns the tree as a string, one line per label.
+    pub fn tree_report(&self) -> String {
+        let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut roots = Vec::new();
+        for (label, _) in self.sorted_stats() {
+            match self.parents.get(label) {
+                Some(parent) if self.stats.contains_key(parent) => {
+                    children.entry(parent.as_str()).or_default().push(label);
+                }
+                _ => roots.push(label),
+            }
+        }
+        let mut out = String::new();
+        // Entries without a label are `<self>` nodes holding their time in milliseconds.
+        let mut stack: Vec<(Option<&str>, usize, f64)> = roots
+            .into_iter()
+            .rev()
+            .map(|label| (Some(label), 0, 0.0))
+            .collect();
+        while let Some((label, depth, self_ms)) = stack.pop() {
+            let indent = "  ".repeat(depth);
+            let Some(label) = label else {
+                out.push_str(&format!(
+                    "{}<self>: {}ms\n",
+                    indent,
+                    self.format_ms(self_ms)
+                ));
+                continue;
+            };
+            let stats = &self.stats[label];
+            out.push_str(&format!(
+                "{}{}: {}ms ({} calls)\n",
+                indent,
+                self.display_label(label),
+                self.format_ms(stats.total_ms()),
+                stats.count()
+            ));
+            // A label can only be visited once, which also guards against parent cycles.
+            if let Some(kids) = children.remove(label) {
+                let children_ms: f64 = kids.iter().map(|kid| self.stats[*kid].total_ms()).sum();
+                let self_ms = (stats.total_ms() - children_ms).max(0.0);
+                stack.push((None, depth + 1, self_ms));
+                stack.extend(
+                    kids.into_iter()
+                        .rev()
+                        .map(|kid| (Some(kid), depth + 1, 0.0)),
+                );
+            }
+        }
+        out
+    }
+
+    /// Prints the tree produced by `tree_report`.
+    ///
+    /// The tree goes to the configured writer like every other printed line.
+    pub fn print_tree(&self) {
+        for line in self.tree_report().lines() {
+            #[cfg(not(target_arch = "wasm32"))]
+            self.output().emit(line.to_string());
+            #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+            web_sys::console::log_1(&line.into());
+        }
+    }
+
+    /// Starts a timer that reports its runtime as a share of a running parent timer.
+    ///
+    /// When the returned guard is dropped, the timer is ended and printed as
+    /// `child: 12.000ms (30% of parent)`, comparing against the parent's elapsed time so far.
+    /// This gives inline context without a full summary pass. The parent is recorded as the
+    /// child's parent, like for nested `scope` guards.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    /// * `parent` - The label of the running parent timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `ScopeGuard` that ends the timer on drop.
+    pub fn scope_within(&mut self, label: &str, parent: &str) -> ScopeGuard<'_, S> {
+        let key = self.label_key(label).into_owned();
+        let parent = self.label_key(parent).into_owned();
+        if ENABLED && !self.timers.contains_key(&parent) {
+            #[cfg(not(target_arch = "wasm32"))]
+            self.warn_missing(&parent);
+        }
+        if parent != key {
+            self.parents.insert(key, parent.clone());
+        }
+        let index = self.enter_scope(label);
+        ScopeGuard::within(self, label, index, &parent)
+    }
+
+    /// Ends a timer and prints its runtime as a share of a parent timer's elapsed time.
+    ///
+    /// Falls back to the plain `time_end` output if the parent isn't running.
+    pub(crate) fn time_end_within(&mut self, label: &str, parent: &str) -> f64 {
+        let key = self.label_key(label).into_owned();
+        if !self.timers.contains_key(&key) {
+            return self.time_end(label, false);
+        }
+        let ms = self.time_end(label, true);
+        if self.should_print(&key, false) {
+            let message = match self.running_ms(parent) {
+                Some(parent_ms) => format!(
+                    "{} ({:.0}% of {})",
+                    self.end_message(&key, ms),
+                    percent_of(ms, parent_ms),
+                    self.display_label(parent)
+                ),
+                None => self.end_message(&key, ms),
+            };
+            #[cfg(not(target_arch = "wasm32"))]
+            self.emit(&key, message);
+            #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+            web_sys::console::log_1(&message.into());
+        }
+        ms
+    }
+
+    /// Returns the parent label recorded for a nested timer.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the label of the enclosing timer, or `None` if the timer has no recorded parent.
+    pub fn parent(&self, label: &str) -> Option<&str> {
+        self.parents
+            .get(self.label_key(label).as_ref())
+            .map(String::as_str)
+    }
+
+    /// Pauses a running timer.
+    ///
+    /// Time spent paused is excluded from the timer's elapsed time. Logging or ending a paused
+    /// timer reports the running time accumulated up to the pause.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the timer was running and is now paused.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pause(&mut self, label: &str) -> bool {
+        if !ENABLED {
+            return false;
+        }
+        let key = self.label_key(label);
+        let label = key.as_ref();
+
+        if !self.timers.contains_key(label) {
+            self.warn_missing(label);
+            return false;
+        }
+        if self.paused.contains_key(label) {
+            return false;
+        }
+        self.paused.insert(label.to_string(), Instant::now());
+        true
+    }
+
+    /// Resumes a paused timer.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the timer was paused and is now running again.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn resume(&mut self, label: &str) -> bool {
+        if !ENABLED {
+            return false;
+        }
+        let key = self.label_key(label);
+        let label = key.as_ref();
+
+        let Some(paused_at) = self.paused.remove(label) else {
+            if !self.timers.contains_key(label) {
+                self.warn_missing(label);
+            }
+            return false;
+        };
+        if let Some(start_time) = self.timers.get_mut(label) {
+            // Shift the start forward so the paused interval is not counted.
+            let paused_for = paused_at.elapsed();
+            *start_time += paused_for;
+            *self.paused_total.entry(label.to_string()).or_default() += paused_for;
+        }
+        true
+    }
+
+    /// Returns whether a timer is currently paused.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(true)` if the timer is paused, `Some(false)` if it is running, or `None` if it doesn't exist.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn is_paused(&self, label: &str) -> Option<bool> {
+        let key = self.label_key(label);
+        let label = key.as_ref();
+
+        self.timers
+            .contains_key(label)
+            .then(|| self.paused.contains_key(label))
+    }
+
+    /// Returns the share of a running timer's lifetime spent paused.
+    ///
+    /// A high ratio marks a wait-bound operation, e.g. one paused while waiting on I/O, and a
+    /// low one a compute-bound operation. The ratio is taken of raw clock time, before the
+    /// scale factor.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns paused time divided by the time since the timer was started, between 0.0 and
+    /// 1.0, or `None` if the timer isn't running.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn paused_ratio(&self, label: &str) -> Option<f64> {
+        let key = self.label_key(label);
+        let label = key.as_ref();
+        let &start_time = self.timers.get(label)?;
+        let now = Instant::now();
+        let running = self.running_time(label, start_time, now);
+        Some(self.idle_ratio(label, running, now).unwrap_or(0.0))
+    }
+
+    /// Sets whether `time_end` reports how much of a paused timer's lifetime was spent paused.
+    ///
+    /// When enabled, timers that were paused at least once end with e.g. `parse: 12.000ms
+    /// (45% idle)`. Disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to report the paused share.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_show_idle(&mut self, enabled: bool) {
+        self.show_idle = enabled;
+    }
+
+    /// Returns the paused share of a timer's lifetime at `now`, or `None` if it was never paused.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn idle_ratio(&self, label: &str, running: Duration, now: Instant) -> Option<f64> {
+        let current = self
+            .paused
+            .get(label)
+            .map(|&paused_at| now.saturating_duration_since(paused_at));
+        let earlier = self.paused_total.get(label).copied();
+        if current.is_none() && earlier.is_none() {
+            return None;
+        }
+        let idle = current.unwrap_or_default() + earlier.unwrap_or_default();
+        let lifetime = idle + running;
+        if lifetime.is_zero() {
+            return Some(0.0);
+        }
+        Some(idle.as_secs_f64() / lifetime.as_secs_f64())
+    }
+
+    /// Sets how often the "Timer does not exist" warning is printed for a label.
+    ///
+    /// A hot loop that queries a missing label prints the warning on every iteration by
+    /// default; `Once` or `RateLimited` keep stderr readable while still surfacing the problem.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The warning mode.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_missing_timer_warning(&mut self, mode: MissingTimerWarning) {
+        self.missing_warning = mode;
+        self.warned
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Prints the missing-timer warning for a label, subject to the warning mode.
+    ///
+    /// Returns `true` if the warning was printed.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn warn_missing(&self, label: &str) -> bool {
+        let print = match self.missing_warning {
+            MissingTimerWarning::Always => true,
+            mode => {
+                let now = Instant::now();
+                let mut warned = self.warned.lock().unwrap_or_else(|e| e.into_inner());
+                let print = match (mode, warned.get(label)) {
+                    (_, None) => true,
+                    (MissingTimerWarning::RateLimited(interval), Some(&last)) => {
+                        now.duration_since(last) >= interval
+                    }
+                    _ => false,
+                };
+                if print {
+                    warned.insert(label.to_string(), now);
+                }
+                print
+            }
+        };
+        if print {
+            eprintln!("Timer '{}' does not exist", label);
+        }
+        print
+    }
+
+    /// Scales every reported elapsed value by a factor.
+    ///
+    /// This is a presentation-only transform for "what if this were twice as fast" discussions:
+    /// time is still measured normally and start instants are untouched, but the values that
+    /// are printed, returned and recorded in statistics are multiplied by `factor`.
+    /// The default factor is 1.0. Negative or non-finite factors are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - The multiplier for reported values.
+    pub fn set_scale(&mut self, factor: f64) {
+        if factor.is_finite() && factor >= 0.0 {
+            self.scale = factor;
+        } else {
+            eprintln!("Ignoring invalid timer scale {}", factor);
+        }
+    }
+
+    /// Sets the smallest elapsed value a measurement reports.
+    ///
+    /// On platforms with a coarse `Instant`, tiny operations otherwise report misleadingly
+    /// precise values. Any measured elapsed below `floor` is reported as `floor` instead,
+    /// before the scale factor is applied. The default floor is zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `floor` - The smallest elapsed value to report.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_resolution_floor(&mut self, floor: Duration) {
+        self.resolution_floor = floor;
+    }
+
+    /// Applies the resolution floor and the configured scale factor to a measured duration.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn scaled(&self, duration: Duration) -> Duration {
+        let duration = duration.max(self.resolution_floor);
+        if self.scale == 1.0 {
+            duration
+        } else {
+            duration.mul_f64(self.scale)
+        }
+    }
+
+    /// Returns how long a timer has been running at `now`, excluding time spent paused.
+    ///
+    /// If `now` is before the start, e.g. after a backward jump of a non-monotonic platform
+    /// clock, the result is clamped to zero instead of underflowing.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn running_time(&self, label: &str, start_time: Instant, now: Instant) -> Duration {
+        let end = match self.paused.get(label) {
+            Some(&paused_at) => paused_at.min(now),
+            None => now,
+        };
+        end.saturating_duration_since(start_time)
+    }
+
+    /// Limits the length of timer labels.
+    ///
+    /// Labels built from dynamic data (such as request IDs) can grow the timer map without
+    /// bound. With a limit set, `time` warns about longer labels and either truncates or rejects
+    /// them according to `policy`. Truncated labels are looked up by their truncated form, so
+    /// the original label can still be passed to `time_end` and the other methods.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_len` - The maximum label length in characters, or `None` to remove the limit.
+    /// * `policy` - What to do with labels longer than `max_len`.
+    pub fn set_max_label_len(&mut self, max_len: Option<usize>, policy: LabelLengthPolicy) {
+        self.max_label_len = max_len.map(|max_len| (max_len, policy));
+    }
+
+    /// Installs a function that normalizes every label before it is stored or looked up.
+    ///
+    /// Normalization prevents near-identical labels such as `" parse "` and `"parse"` from
+    /// fragmenting into separate timers and statistics. The default is the identity.
+    ///
+    /// ```
+    /// use timelog::Timer;
+    ///
+    /// let mut timer = Timer::new();
+    /// timer.set_label_normalizer(Box::new(Timer::trim_lowercase));
+    /// timer.time(" Parse ");
+    /// // Ends the timer started above.
+    /// timer.time_end("parse", false);
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `normalizer` - The function mapping a label to its normalized form.
+    pub fn set_label_normalizer(&mut self, normalizer: LabelNormalizer) {
+        self.label_normalizer = Some(normalizer);
+    }
+
+    /// Removes the label normalizer, restoring the identity mapping.
+    pub fn clear_label_normalizer(&mut self) {
+        self.label_normalizer = None;
+    }
+
+    /// Installs a function that maps labels to display names in printed output.
+    ///
+    /// Unlike `set_label_normalizer`, this leaves stored keys and statistics untouched: a timer
+    /// stored as `db.q1` can print as `Database Query 1` while `stats("db.q1")` still finds it.
+    /// The default is the identity.
+    ///
+    /// # Arguments
+    ///
+    /// * `mapper` - The function mapping a stored label to its display name.
+    pub fn set_display_mapper(&mut self, mapper: LabelNormalizer) {
+        self.display_mapper = Some(mapper);
+    }
+
+    /// Removes the display mapper, printing labels as they are stored.
+    pub fn clear_display_mapper(&mut self) {
+        self.display_mapper = None;
+    }
+
+    /// Applies the display mapper, if one is installed.
+    fn display_label<'a>(&self, label: &'a str) -> Cow<'a, str> {
+        match &self.display_mapper {
+            Some(mapper) => Cow::Owned(mapper(label)),
+            None => Cow::Borrowed(label),
+        }
+    }
+
+    /// Applies the label normalizer, if one is installed.
+    fn normalize_label<'a>(&self, label: &'a str) -> Cow<'a, str> {
+        match &self.label_normalizer {
+            Some(normalizer) => Cow::Owned(normalizer(label)),
+            None => Cow::Borrowed(label),
+        }
+    }
+
+    /// Truncates a normalized label to the maximum length, if truncation is configured.
+    fn truncate_label<'a>(&self, label: Cow<'a, str>) -> Cow<'a, str> {
+        match self.max_label_len {
+            Some((max_len, LabelLengthPolicy::Truncate)) => match label.char_indices().nth(max_len)
+            {
+                Some((end, _)) => Cow::Owned(label[..end].to_string()),
+                None => label,
+            },
+            _ => label,
+        }
+    }
+
+    /// Returns the key under which a label is stored.
+    fn label_key<'a>(&self, label: &'a str) -> Cow<'a, str> {
+        self.truncate_label(self.normalize_label(label))
+    }
+
+    /// Formats the message printed when a timer ends.
+    ///
+    /// If a budget is set for the label, the share of the budget consumed is appended,
+    /// e.g. `parse: 36.500ms (73% of 50ms budget)`, followed by a warning when the budget
+    /// is exceeded, e.g. `parse: 60.000ms (120% of 50ms budget, over by 10.000ms)`.
+    /// If a baseline is set, the ratio to it is appended, e.g. `parse: 52.000ms (1.3x baseline
+    /// 40ms)`.
+    ///
+    /// If the timer was started with `time_here!`, its call site follows the label, e.g.
+    /// `parse (src/main.rs:42): 12.000ms`.
+    /// If thresholds are set, the message is prefixed with its severity marker,
+    /// e.g. `[WARN] parse: 36.500ms`.
+    fn end_message(&self, label: &str, ms: f64) -> String {
+        self.end_message_at(label, ms, None)
+    }
+
+    /// Like `end_message`, but prints the elapsed time as `+1234ms @ offset 5678ms` when the
+    /// offset of the end from the epoch is given.
+    fn end_message_at(&self, label: &str, ms: f64, offset_ms: Option<f64>) -> String {
+        let elapsed = match offset_ms {
+            Some(offset_ms) => format!(
+                "+{}ms @ offset {}ms",
+                self.format_ms(ms),
+                self.format_ms(offset_ms)
+            ),
+            None => format!("{}ms", self.format_ms(ms)),
+        };
+        let name = match self.locations.get(label) {
+            Some(location) => format!(
+                "{} ({}:{})",
+                self.display_label(label),
+                location.file(),
+                location.line()
+            ),
+            None => self.display_label(label).into_owned(),
+        };
+        let message = match self.budgets.get(label) {
+            Some(&budget) if budget > 0.0 && ms > budget => format!(
+                "{}: {} ({:.0}% of {}ms budget, over by {}ms)",
+                name,
+                elapsed,
+                ms / budget * 100.0,
+                budget,
+                self.format_ms(ms - budget)
+            ),
+            Some(&budget) if budget > 0.0 => format!(
+                "{}: {} ({:.0}% of {}ms budget)",
+                name,
+                elapsed,
+                ms / budget * 100.0,
+                budget
+            ),
+            _ => format!("{}: {}", name, elapsed),
+        };
+        let message = match self.baselines.get(label) {
+            Some(&baseline) if baseline > 0.0 => format!(
+                "{} ({:.1}x baseline {}ms)",
+                message,
+                ms / baseline,
+                baseline
+            ),
+            _ => message,
+        };
+        match self.severity(ms) {
+            Some(severity) => format!("{} {}", severity.marker(), message),
+            None => message,
+        }
+    }
+
+    /// Sets the epoch that ended timers report their end offset from, for log correlation.
+    ///
+    /// With an epoch such as the receipt of a request, `time_end` prints
+    /// `label: +1234ms @ offset 5678ms`, where the offset is the time from the epoch to the end
+    /// of the timer, which places each timing on the request's timeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - The reference instant.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_epoch(&mut self, epoch: Instant) {
+        self.epoch = Some(epoch);
+    }
+
+    /// Sets the separator inserted between groups of three integer digits of printed values.
+    ///
+    /// With `Some(',')`, `1234.567` prints as `1,234.567`. The default is `None`, no grouping,
+    /// which keeps output machine-readable. Machine-oriented formats such as logfmt always
+    /// print plain numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `separator` - The grouping separator, or `None` to disable grouping.
+    #[cfg(feature = "num-format")]
+    pub fn set_thousands_separator(&mut self, separator: Option<char>) {
+        self.thousands_separator = separator;
+    }
+
+    /// Sets the separator between the integer and fractional digits of printed values.
+    ///
+    /// Together with `set_thousands_separator(Some('.'))`, `set_decimal_separator(',')`
+    /// prints `1234.567` as `1.234,567`. The default is `.`.
+    ///
+    /// # Arguments
+    ///
+    /// * `separator` - The decimal separator.
+    #[cfg(feature = "num-format")]
+    pub fn set_decimal_separator(&mut self, separator: char) {
+        self.decimal_separator = separator;
+    }
+
+    /// Formats a millisecond value with three decimals for printing.
+    ///
+    /// With the `num-format` feature, the configured separators are applied.
+    fn format_ms(&self, ms: f64) -> String {
+        let plain = format!("{:.3}", ms);
+        #[cfg(feature = "num-format")]
+        if ms.is_finite() {
+            let (sign, digits) = match plain.strip_prefix('-') {
+                Some(digits) => ("-", digits),
+                None => ("", plain.as_str()),
+            };
+            let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+            let mut out = String::with_capacity(plain.len() + int.len() / 3 + 1);
+            out.push_str(sign);
+            for (i, digit) in int.chars().enumerate() {
+                if i > 0 && (int.len() - i) % 3 == 0 {
+                    if let Some(separator) = self.thousands_separator {
+                        out.push(separator);
+                    }
+                }
+                out.push(digit);
+            }
+            out.push(self.decimal_separator);
+            out.push_str(frac);
+            return out;
+        }
+        plain
+    }
+
+    /// Sets the thresholds that classify every completed timing into a severity band.
+    ///
+    /// Timings below `warn_ms` are `Ok`, timings below `error_ms` are `Warn` and the rest
+    /// are `Error`. `time_end` prefixes its output with the matching `[OK]`, `[WARN]` or
+    /// `[ERROR]` marker and, with the `log` feature, also logs it at the matching level.
+    ///
+    /// # Arguments
+    ///
+    /// * `warn_ms` - The smallest elapsed time in milliseconds classified as `Warn`.
+    /// * `error_ms` - The smallest elapsed time in milliseconds classified as `Error`.
+    pub fn set_thresholds(&mut self, warn_ms: f64, error_ms: f64) {
+        self.thresholds = Some((warn_ms, error_ms));
+    }
+
+    /// Removes the thresholds, so timings are no longer classified.
+    pub fn clear_thresholds(&mut self) {
+        self.thresholds = None;
+    }
+
+    /// Classifies an elapsed time against the thresholds.
+    ///
+    /// # Arguments
+    ///
+    /// * `ms` - The elapsed time in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// Returns the severity of the timing, or `None` if no thresholds are set.
+    pub fn severity(&self, ms: f64) -> Option<Severity> {
+        let (warn_ms, error_ms) = self.thresholds?;
+        Some(if ms >= error_ms {
+            Severity::Error
+        } else if ms >= warn_ms {
+            Severity::Warn
+        } else {
+            Severity::Ok
+        })
+    }
+
+    /// Sets a time budget for a label.
+    ///
+    /// When a budget is set, `time_end` reports the share of the budget the timer consumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `budget_ms` - The budget in milliseconds.
+    pub fn set_budget(&mut self, label: &str, budget_ms: f64) {
+        let key = self.label_key(label);
+        let label = key.as_ref();
+
+        self.budgets.insert(label.to_string(), budget_ms);
+    }
+
+    /// Sets a reference time for a label to surface regressions inline.
+    ///
+    /// When a baseline is set, `time_end` reports how the timing compares to it, e.g.
+    /// `db_query: 52.000ms (1.3x baseline 40ms)`. Baselines can come from a previous run, e.g.
+    /// the means of a `TimerState` saved with `save_state`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `baseline_ms` - The reference time in milliseconds.
+    pub fn set_baseline(&mut self, label: &str, baseline_ms: f64) {
+        let label = self.label_key(label).into_owned();
+        self.baselines.insert(label, baseline_ms);
+    }
+
+    /// Loads the best times of previous runs written by `save_best`.
+    ///
+    /// Loaded times are merged with those already loaded, keeping the minimum of each label.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to load.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if the file can't be read or a line is malformed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_best(&mut self, path: &Path) -> std::io::Result<()> {
+        let contents = std::fs::read_to_string(path)?;
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            let parsed = line
+                .split_once(' ')
+                .and_then(|(ms, label)| Some((ms.parse::<f64>().ok()?, label)));
+            let Some((ms, label)) = parsed else {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("malformed best time line '{}'", line),
+                ));
+            };
+            let best = self.best.entry(label.to_string()).or_insert(ms);
+            *best = best.min(ms);
+        }
+        Ok(())
+    }
+
+    /// Returns the best time a label has ever achieved.
+    ///
+    /// This is the minimum of the best time loaded with `load_best` and the fastest
+    /// measurement of the current run.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the best time in milliseconds, or `None` if the label has no loaded best time
+    /// and has never been ended.
+    pub fn best_ms(&self, label: &str) -> Option<f64> {
+        let key = self.label_key(label);
+        let loaded = self.best.get(key.as_ref()).copied();
+        let current = self.stats.get(key.as_ref()).map(TimerStats::min_ms);
+        match (loaded, current) {
+            (Some(loaded), Some(current)) => Some(loaded.min(current)),
+            (best, None) | (None, best) => best,
+        }
+    }
+
+    /// Returns whether the current run beat a label's loaded best time.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the fastest measurement of this run is faster than the loaded best
+    /// time, or if the label has been ended but has no loaded best time yet.
+    pub fn is_new_best(&self, label: &str) -> bool {
+        let key = self.label_key(label);
+        match (self.stats.get(key.as_ref()), self.best.get(key.as_ref())) {
+            (Some(stats), Some(&best)) => stats.min_ms() < best,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// Writes the best time of every label, loaded or measured, to a file.
+    ///
+    /// Each line holds a time in milliseconds and a label, e.g. `12.5 parse`. Load the file
+    /// with `load_best` in a later run to track whether it gets faster.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to write, replaced if it exists.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if the file can't be written.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_best(&self, path: &Path) -> std::io::Result<()> {
+        let mut labels: Vec<&str> = self
+            .best
+            .keys()
+            .chain(self.stats.keys())
+            .map(String::as_str)
+            .collect();
+        labels.sort_unstable();
+        labels.dedup();
+        let mut out = String::new();
+        for label in labels {
+            if let Some(ms) = self.best_ms(label) {
+                out.push_str(&format!("{} {}\n", ms, label));
+            }
+        }
+        std::fs::write(path, out)
+    }
+
+    /// Sets time budgets for many labels at once.
+    ///
+    /// This lets an application load its latency targets from a config file at startup.
+    /// Budgets of labels not in `budgets` are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `budgets` - The budgets in milliseconds, keyed by label.
+    pub fn set_budgets(&mut self, budgets: HashMap<String, f64>) {
+        for (label, budget_ms) in budgets {
+            self.set_budget(&label, budget_ms);
+        }
+    }
+
+    /// Returns the time budget of a label.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the budget in milliseconds, or `None` if no budget is set.
+    pub fn budget(&self, label: &str) -> Option<f64> {
+        self.budgets.get(self.label_key(label).as_ref()).copied()
+    }
+
+    /// Returns how much of a running timer's budget is left.
+    ///
+    /// This lets deadline-driven code decide whether to continue or bail out. The result
+    /// goes negative once the budget is overrun.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the running timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the budget minus the elapsed time in milliseconds, or `None` if the label has
+    /// no budget or isn't running.
+    pub fn remaining_ms(&self, label: &str) -> Option<f64> {
+        let key = self.label_key(label);
+        let budget = *self.budgets.get(key.as_ref())?;
+        Some(budget - self.running_ms(&key)?)
+    }
+
+    /// Ends a batch of timers in one call and prints their runtimes.
+    ///
+    /// # Arguments
+    ///
+    /// * `labels` - The labels of the timers to end.
+    ///
+    /// # Returns
+    ///
+    /// Returns the elapsed milliseconds for each label, in the same order as `labels`.
+    /// Missing timers are reported like in `time_end` and yield 0.0.
+    pub fn time_end_many(&mut self, labels: &[&str]) -> Vec<f64> {
+        labels
+            .iter()
+            .map(|label| self.time_end(label, false))
+            .collect()
+    }
+
+    /// Folds a completed measurement into the label's statistics and retained samples.
+    fn record(&mut self, label: &str, duration: Duration) {
+        let ms = Timer::duration_to_ms(duration);
+        if let Some(frame) = self.frame.as_mut() {
+            frame.push((label.to_string(), ms));
+        }
+        self.stats
+            .entry(label.to_string())
+            .or_default()
+            .record(duration);
+        if self.retain_samples {
+            self.samples.entry(label.to_string()).or_default().push(ms);
+        }
+        #[cfg(feature = "approx-quantiles")]
+        self.quantiles
+            .entry(label.to_string())
+            .or_default()
+            .record(ms);
+    }
+
+    /// Starts collecting the timings of a new frame, e.g. one tick of a game loop.
+    ///
+    /// Every timing completed until `end_frame` is collected into the frame, in addition to
+    /// the label's accumulated statistics. Timings collected for an unfinished frame are
+    /// discarded.
+    pub fn begin_frame(&mut self) {
+        self.frame = Some(Vec::new());
+    }
+
+    /// Ends the current frame and returns its timings.
+    ///
+    /// Timers still running keep running and complete into the frame they end in.
+    ///
+    /// # Returns
+    ///
+    /// Returns the timings completed since `begin_frame`, which are empty if no frame was
+    /// begun.
+    pub fn end_frame(&mut self) -> FrameReport {
+        let report = FrameReport {
+            frame: self.frames,
+            timings: self.frame.take().unwrap_or_default(),
+        };
+        self.frames += 1;
+        report
+    }
+
+    /// Enables or disables retention of individual measurements.
+    ///
+    /// Retained samples are needed by `histogram`. They grow without bound, one `f64` per
+    /// completed measurement, so only enable retention when needed. Disabling retention
+    /// discards the samples retained so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to retain samples.
+    pub fn set_retain_samples(&mut self, enabled: bool) {
+        self.retain_samples = enabled;
+        if !enabled {
+            self.samples.clear();
+        }
+    }
+
+    /// Builds a cumulative histogram of a label's retained samples.
+    ///
+    /// Buckets follow the Prometheus convention: each bucket counts every sample less than or
+    /// equal to its upper bound, and a final `+Inf` bucket counts all samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `buckets_ms` - The bucket upper bounds in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// Returns `(upper_bound, cumulative_count)` pairs in ascending bound order, ending with
+    /// `f64::INFINITY`. Counts are zero if no samples of the label are retained.
+    pub fn histogram(&self, label: &str, buckets_ms: &[f64]) -> Vec<(f64, u64)> {
+        let key = self.label_key(label);
+        let samples = self
+            .samples
+            .get(key.as_ref())
+            .map_or(&[][..], Vec::as_slice);
+        let mut bounds = buckets_ms.to_vec();
+        bounds.sort_by(f64::total_cmp);
+        bounds.push(f64::INFINITY);
+        bounds
+            .into_iter()
+            .map(|bound| {
+                let count = samples.iter().filter(|&&ms| ms <= bound).count();
+                (bound, count as u64)
+            })
+            .collect()
+    }
+
+    /// Returns a sorted copy of a label's retained samples.
+    ///
+    /// This gives raw access for analysis the built-in statistics don't offer, e.g. plotting
+    /// a CDF. Samples are only retained while `set_retain_samples` is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the retained measurements in milliseconds in ascending order, or `None` if no
+    /// samples of the label are retained.
+    pub fn samples(&self, label: &str) -> Option<Vec<f64>> {
+        let mut samples = self.samples.get(self.label_key(label).as_ref())?.clone();
+        samples.sort_by(f64::total_cmp);
+        Some(samples)
+    }
+
+    /// Estimates a percentile of a label's completed measurements.
+    ///
+    /// Every measurement is folded into a streaming sketch, so memory stays bounded no matter
+    /// how many measurements are recorded and samples need not be retained. Estimates are
+    /// within 1% of the exact percentile's value.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `p` - The percentile, between 0.0 and 100.0, e.g. 99.0 for p99.
+    ///
+    /// # Returns
+    ///
+    /// Returns the estimated percentile in milliseconds, or `None` if the label has never
+    /// been ended.
+    #[cfg(feature = "approx-quantiles")]
+    pub fn percentile(&self, label: &str, p: f64) -> Option<f64> {
+        self.quantiles
+            .get(self.label_key(label).as_ref())?
+            .quantile(p / 100.0)
+    }
+
+    /// Returns the accumulated statistics of a label.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the statistics of every completed measurement of the label, or `None` if the label
+    /// has never been ended.
+    pub fn stats(&self, label: &str) -> Option<&TimerStats> {
+        let key = self.label_key(label);
+        let label = key.as_ref();
+
+        self.stats.get(label)
+    }
+
+    /// Returns the accumulated statistics of every label as owned data.
+    ///
+    /// This decouples the data from `report`'s presentation, so it can be sorted, filtered or
+    /// forwarded freely.
+    ///
+    /// # Returns
+    ///
+    /// Returns `(label, stats)` pairs sorted by label.
+    pub fn all_stats(&self) -> Vec<(String, TimerStats)> {
+        self.sorted_stats()
+            .into_iter()
+            .map(|(label, stats)| (label.to_string(), *stats))
+            .collect()
+    }
+
+    /// Returns the accumulated statistics of every label and resets them in one step.
+    ///
+    /// This is the report-and-reset pattern for periodic metrics: no measurement can be
+    /// recorded between reading and resetting the statistics, so none is lost or counted twice.
+    /// Retained samples and quantile estimates are reset too; running timers are unaffected.
+    ///
+    /// # Returns
+    ///
+    /// Returns a map from each label to its statistics since the last drain.
+    pub fn drain_stats(&mut self) -> HashMap<String, TimerStats> {
+        self.samples.clear();
+        #[cfg(feature = "approx-quantiles")]
+        self.quantiles.clear();
+        std::mem::take(&mut self.stats)
+    }
+
+    /// Returns the sample standard deviation of a label's measurements.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the standard deviation in milliseconds, or `None` if fewer than two
+    /// measurements of the label have been recorded.
+    pub fn stddev_ms(&self, label: &str) -> Option<f64> {
+        self.stats(label)
+            .filter(|stats| stats.count() >= 2)
+            .map(TimerStats::std_dev_ms)
+    }
+
+    /// Returns the coefficient of variation of a label's measurements.
+    ///
+    /// This is the standard deviation divided by the mean, a unitless measure of how noisy a
+    /// label is, comparable across labels of very different magnitudes.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the coefficient of variation, or `None` if fewer than two measurements of the
+    /// label have been recorded or their mean is zero.
+    pub fn cv(&self, label: &str) -> Option<f64> {
+        let mean = self.stats(label)?.mean_ms();
+        if mean == 0.0 {
+            return None;
+        }
+        Some(self.stddev_ms(label)? / mean)
+    }
+
+    /// Returns the ratio of two labels' elapsed times.
+    ///
+    /// Each label is represented by the mean of its accumulated statistics or, if it has
+    /// never been ended, by the current elapsed time of its running timer.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The label of the numerator.
+    /// * `b` - The label of the denominator.
+    ///
+    /// # Returns
+    ///
+    /// Returns `elapsed(a) / elapsed(b)`, or `None` if either label is unknown or `b` took no time.
+    pub fn ratio(&self, a: &str, b: &str) -> Option<f64> {
+        let a = self.comparable_ms(a)?;
+        let b = self.comparable_ms(b)?;
+        (b > 0.0).then(|| a / b)
+    }
+
+    /// Returns whichever of two labels was faster.
+    ///
+    /// Labels are compared like in `ratio`. If both took the same time, `a` is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The label of the first timer.
+    /// * `b` - The label of the second timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the faster label, or `None` if either label is unknown.
+    pub fn faster_of<'a>(&self, a: &'a str, b: &'a str) -> Option<&'a str> {
+        let a_ms = self.comparable_ms(a)?;
+        let b_ms = self.comparable_ms(b)?;
+        Some(if a_ms <= b_ms { a } else { b })
+    }
+
+    /// Returns a label's mean elapsed time, falling back to its running time.
+    fn comparable_ms(&self, label: &str) -> Option<f64> {
+        match self.stats(label) {
+            Some(stats) => Some(stats.mean_ms()),
+            None => self.running_ms(&self.label_key(label)),
+        }
+    }
+
+    /// Returns the reported elapsed time of a running timer, looked up by its stored key.
+    fn running_ms(&self, key: &str) -> Option<f64> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.timers.get(key).map(|&start_time| {
+            let duration = self.scaled(self.running_time(key, start_time, Instant::now()));
+            Timer::duration_to_ms(duration)
+        });
+
+        #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+        return self
+            .timers
+            .get(key)
+            .map(|start_time| (self.performance.now() - start_time).max(0.0) * self.scale);
+
+        #[cfg(all(target_arch = "wasm32", feature = "webworker"))]
+        None
+    }
+
+    /// Wraps an iterator so the time taken to produce each item is recorded under a label.
+    ///
+    /// This instruments pull-based pipelines without starting and ending a timer per item.
+    /// Once the iterator is consumed, `stats` returns the item count, total and mean per item.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label to record the items under.
+    /// * `iter` - The iterator to time.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `TimedIter` yielding the same items as `iter`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn timed_iter<I: IntoIterator>(
+        &mut self,
+        label: &str,
+        iter: I,
+    ) -> TimedIter<'_, I::IntoIter, S> {
+        TimedIter::new(self, label, iter.into_iter())
+    }
+
+    /// Benchmarks a closure by running it repeatedly and prints a summary.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label to record the measurements under.
+    /// * `iterations` - The number of measured runs.
+    /// * `f` - The closure to benchmark.
+    ///
+    /// # Returns
+    ///
+    /// Returns the statistics of the measured runs.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn bench<F: FnMut()>(&mut self, label: &str, iterations: usize, f: F) -> TimerStats {
+        self.bench_with_warmup(label, 0, iterations, f)
+    }
+
+    /// Benchmarks a closure after a number of untimed warmup runs and prints a summary.
+    ///
+    /// Warmup runs give caches, the branch predictor and the allocator a chance to settle, so the
+    /// measured runs are more stable. They are not included in the returned statistics.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label to record the measurements under.
+    /// * `warmup` - The number of untimed runs before measuring.
+    /// * `iterations` - The number of measured runs.
+    /// * `f` - The closure to benchmark.
+    ///
+    /// # Returns
+    ///
+    /// Returns the statistics of the measured runs.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn bench_with_warmup<F: FnMut()>(
+        &mut self,
+        label: &str,
+        warmup: usize,
+        iterations: usize,
+        mut f: F,
+    ) -> TimerStats {
+        if !ENABLED {
+            return TimerStats::default();
+        }
+        for _ in 0..warmup {
+            f();
+        }
+        let key = self.label_key(label);
+        let label = key.as_ref();
+        let mut run = TimerStats::default();
+        for _ in 0..iterations {
+            let start_time = Instant::now();
+            f();
+            let duration = self.scaled(start_time.elapsed());
+            run.record(duration);
+            self.record(label, duration);
+        }
+        self.emit(
+            label,
+            format!(
+                "{}: {} iterations, mean {}ms, min {}ms, max {}ms",
+                self.display_label(label),
+                run.count(),
+                self.format_ms(run.mean_ms()),
+                self.format_ms(run.min_ms()),
+                self.format_ms(run.max_ms())
+            ),
+        );
+        run
+    }
+
+    /// Increments an event counter by one.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the counter.
+    pub fn count(&self, name: &str) {
+        self.count_by(name, 1);
+    }
+
+    /// Increments an event counter by `n`.
+    ///
+    /// Counters are independent of timers and are created on first use. They are listed next
+    /// to the timers by `report` and `print_summary`, as rows without timing fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the counter.
+    /// * `n` - The amount to add.
+    pub fn count_by(&self, name: &str, n: u64) {
+        if !ENABLED {
+            return;
+        }
+        {
+            let counters = self.counters.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(counter) = counters.get(name) {
+                counter.fetch_add(n, Ordering::Relaxed);
+                return;
+            }
+        }
+        let mut counters = self.counters.write().unwrap_or_else(|e| e.into_inner());
+        counters
+            .entry(name.to_string())
+            .or_insert_with(|| AtomicU64::new(0))
+            .fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Returns the current value of an event counter.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the counter.
+    ///
+    /// # Returns
+    ///
+    /// Returns the counter's value, or `None` if it has never been incremented.
+    pub fn counter(&self, name: &str) -> Option<u64> {
+        let counters = self.counters.read().unwrap_or_else(|e| e.into_inner());
+        counters
+            .get(name)
+            .map(|counter| counter.load(Ordering::Relaxed))
+    }
+
+    /// Builds a human-readable report of all accumulated statistics and counters.
+    ///
+    /// Timers and counters are listed in separate sections, each sorted by name.
+    /// Each timer shows its share of the summed totals of all timers.
+    /// Empty sections are omitted.
+    ///
+    /// # Returns
+    ///
+    /// Returns the report as a string, one line per label.
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let stats = self.sorted_stats();
+        if !stats.is_empty() {
+            let grand_total = self.grand_total_ms();
+            out.push_str("Timers:\n");
+            for (label, stats) in stats {
+                out.push_str(&self.report_line(&self.display_label(label), stats, grand_total));
+            }
+        }
+        let counters = self.sorted_counters();
+        if !counters.is_empty() {
+            out.push_str("Counters:\n");
+            for (name, value) in counters {
+                out.push_str(&format!("  {}: {}\n", name, value));
+            }
+        }
+        out
+    }
+
+    /// Builds a column-aligned table of all accumulated statistics, for reading in a terminal.
+    ///
+    /// The table has a header row and one row per label, sorted by label. Column widths are
+    /// computed from the data; labels are left-aligned and numbers right-aligned.
+    ///
+    /// # Returns
+    ///
+    /// Returns the table as a string, or an empty string if no label has been ended.
+    pub fn report_table(&self) -> String {
+        let stats = self.sorted_stats();
+        if stats.is_empty() {
+            return String::new();
+        }
+        let header = ["Label", "Count", "Total", "Mean", "Min", "Max"].map(String::from);
+        let mut rows = vec![header];
+        for (label, stats) in stats {
+            rows.push([
+                self.display_label(label).into_owned(),
+                stats.count().to_string(),
+                format!("{}ms", self.format_ms(stats.total_ms())),
+                format!("{}ms", self.format_ms(stats.mean_ms())),
+                format!("{}ms", self.format_ms(stats.min_ms())),
+                format!("{}ms", self.format_ms(stats.max_ms())),
+            ]);
+        }
+        let mut widths = [0; 6];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let mut out = String::new();
+        for (i, row) in rows.iter().enumerate() {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .enumerate()
+                .map(|(column, (cell, width))| match column {
+                    0 => format!("{:<width$}", cell),
+                    _ => format!("{:>width$}", cell),
+                })
+                .collect();
+            out.push_str(&cells.join("  "));
+            out.push('\n');
+            if i == 0 {
+                let rule: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+                out.push_str(&rule.join("  "));
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// Prints the summary produced by `report` only if some label is slow.
+    ///
+    /// The threshold applies to the largest accumulated total of any single label, not to the
+    /// sum over all labels, so nested timers are not counted twice. Time the whole unit of
+    /// work, e.g. a request, under one label to compare it against the threshold. This keeps
+    /// fast requests quiet while surfacing slow ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold_ms` - The total in milliseconds a label must exceed to print the summary.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the summary was printed.
+    pub fn report_if_over(&self, threshold_ms: f64) -> bool {
+        let slowest = self
+            .stats
+            .values()
+            .map(TimerStats::total_ms)
+            .fold(f64::NEG_INFINITY, f64::max);
+        if slowest <= threshold_ms {
+            return false;
+        }
+        self.print_summary();
+        true
+    }
+
+    /// Builds the `Timers` section of a report for the labels of a dotted label subtree.
+    ///
+    /// Labels are shown relative to `prefix`, and shares are of the subtree's summed totals.
+    pub(crate) fn report_namespace(&self, prefix: &str) -> String {
+        let stats: Vec<_> = self
+            .sorted_stats()
+            .into_iter()
+            .filter(|(label, _)| in_namespace(label, prefix))
+            .collect();
+        if stats.is_empty() {
+            return String::new();
+        }
+        let grand_total = stats.iter().map(|(_, stats)| stats.total_ms()).sum();
+        let mut out = "Timers:\n".to_string();
+        for (label, stats) in stats {
+            let name = self.display_label(label);
+            let name = name
+                .strip_prefix(prefix)
+                .and_then(|rest| rest.strip_prefix('.'))
+                .unwrap_or(&name);
+            out.push_str(&self.report_line(name, stats, grand_total));
+        }
+        out
+    }
+
+    /// Formats the report line of a label's statistics.
+    fn report_line(&self, name: &str, stats: &TimerStats, grand_total: f64) -> String {
+        format!(
+            "  {}: {} calls, total {}ms, mean {}ms, min {}ms, max {}ms, std dev {}ms, {:.1}% of total\n",
+            name,
+            stats.count(),
+            self.format_ms(stats.total_ms()),
+            self.format_ms(stats.mean_ms()),
+            self.format_ms(stats.min_ms()),
+            self.format_ms(stats.max_ms()),
+            self.format_ms(stats.std_dev_ms()),
+            percent_of(stats.total_ms(), grand_total)
+        )
+    }
+
+    /// Prints the summary produced by `report`.
+    ///
+    /// The summary goes to the configured writer like every other printed line.
+    pub fn print_summary(&self) {
+        for line in self.report().lines() {
+            #[cfg(not(target_arch = "wasm32"))]
+            self.output().emit(line.to_string());
+            #[cfg(all(target_arch = "wasm32", not(feature = "webworker")))]
+            web_sys::console::log_1(&line.into());
+        }
+    }
+
+    /// Builds a JSON report of all accumulated statistics and counters.
+    ///
+    /// The report has the shape
+    /// `{"timers":[{"label":"parse","count":2,"total_ms":3,"mean_ms":1.5,"min_ms":1,"max_ms":2,"std_dev_ms":0.7071067811865476,"variance_ms":0.5,"percent_of_total":100}],"counters":{"hits":3}}`,
+    /// with timers and counters sorted by name.
+    ///
+    /// # Returns
+    ///
+    /// Returns the report as a JSON string.
+    #[cfg(feature = "json")]
+    pub fn report_json(&self) -> String {
+        let grand_total = self.grand_total_ms();
+        let timers: Vec<String> = self
+            .sorted_stats()
+            .into_iter()
+            .map(|(label, stats)| {
+                format!(
+                    "{{\"label\":{},\"count\":{},\"total_ms\":{},\"mean_ms\":{},\"min_ms\":{},\"max_ms\":{},\"std_dev_ms\":{},\"variance_ms\":{},\"percent_of_total\":{}}}",
+                    json::string(label),
+                    stats.count(),
+                    json::number(stats.total_ms()),
+                    json::number(stats.mean_ms()),
+                    json::number(stats.min_ms()),
+                    json::number(stats.max_ms()),
+                    json::number(stats.std_dev_ms()),
+                    json::number(stats.variance_ms()),
+                    json::number(percent_of(stats.total_ms(), grand_total))
+                )
+            })
+            .collect();
+        let counters: Vec<String> = self
+            .sorted_counters()
+            .into_iter()
+            .map(|(name, value)| format!("{}:{}", json::string(&name), value))
+            .collect();
+        format!(
+            "{{\"timers\":[{}],\"counters\":{{{}}}}}",
+            timers.join(","),
+            counters.join(",")
+        )
+    }
+
+    /// Returns the labels with the highest mean elapsed time, slowest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The maximum number of labels to return.
+    ///
+    /// # Returns
+    ///
+    /// Returns up to `n` `(label, mean_ms)` pairs from the accumulated statistics.
+    pub fn top_slowest(&self, n: usize) -> Vec<(String, f64)> {
+        self.top_by(n, |_, stats| stats.mean_ms())
+    }
+
+    /// Returns the labels with the highest cumulative elapsed time, slowest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The maximum number of labels to return.
+    ///
+    /// # Returns
+    ///
+    /// Returns up to `n` `(label, total_ms)` pairs from the accumulated statistics.
+    pub fn top_slowest_by_total(&self, n: usize) -> Vec<(String, f64)> {
+        self.top_by(n, |_, stats| stats.total_ms())
+    }
+
+    /// Sets how much a label matters when ranking by `top_by_weighted_total`.
+    ///
+    /// Weight frequently hit or user-facing paths up, and background work down, to prioritize
+    /// what to optimize. The default weight is 1.0.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `weight` - The factor applied to the label's total.
+    pub fn set_weight(&mut self, label: &str, weight: f64) {
+        let label = self.label_key(label).into_owned();
+        self.weights.insert(label, weight);
+    }
+
+    /// Returns the labels with the highest weighted cumulative elapsed time, slowest first.
+    ///
+    /// Each label's total is multiplied by its weight from `set_weight` before ranking.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The maximum number of labels to return.
+    ///
+    /// # Returns
+    ///
+    /// Returns up to `n` `(label, weighted_total_ms)` pairs from the accumulated statistics.
+    pub fn top_by_weighted_total(&self, n: usize) -> Vec<(String, f64)> {
+        self.top_by(n, |label, stats| {
+            stats.total_ms() * self.weights.get(label).copied().unwrap_or(1.0)
+        })
+    }
+
+    /// Returns the weighted sum of several labels' mean elapsed times.
+    ///
+    /// This rolls sub-operation timings up into a composite indicator, such as a
+    /// user-perceived latency score. Labels that have never been ended contribute nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights` - The labels to sum and the weight of each.
+    ///
+    /// # Returns
+    ///
+    /// Returns the weighted total in milliseconds.
+    pub fn weighted_total(&self, weights: &[(&str, f64)]) -> f64 {
+        weights
+            .iter()
+            .filter_map(|&(label, weight)| Some(self.stats(label)?.mean_ms() * weight))
+            .sum()
+    }
+
+    /// Ranks labels by a statistic in descending order, breaking ties by label.
+    fn top_by(&self, n: usize, key: impl Fn(&str, &TimerStats) -> f64) -> Vec<(String, f64)> {
+        let mut ranked: Vec<(String, f64)> = self
+            .sorted_stats()
+            .into_iter()
+            .map(|(label, stats)| (label.to_string(), key(label, stats)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked.truncate(n);
+        ranked
+    }
+
+    /// Returns the accumulated statistics of every label, sorted by label.
+    fn sorted_stats(&self) -> Vec<(&str, &TimerStats)> {
+        let mut stats: Vec<(&str, &TimerStats)> = self
+            .stats
+            .iter()
+            .map(|(label, stats)| (label.as_str(), stats))
+            .collect();
+        stats.sort_by(|a, b| a.0.cmp(b.0));
+        stats
+    }
+
+    /// Encodes the accumulated statistics, retained samples and counters compactly.
+    ///
+    /// The encoding is a fraction of the size of `report_json` and loads back losslessly with
+    /// `load_binary`, which suits shipping profiling results between processes or persisting
+    /// large baselines. Running timers are not included.
+    ///
+    /// # Returns
+    ///
+    /// Returns the encoded bytes.
+    #[cfg(feature = "binary")]
+    pub fn report_binary(&self) -> Vec<u8> {
+        let mut out = binary::MAGIC.to_vec();
+        let stats = self.sorted_stats();
+        binary::put_u64(&mut out, stats.len() as u64);
+        for (label, stats) in stats {
+            binary::put_str(&mut out, label);
+            stats.encode(&mut out);
+        }
+        let mut samples: Vec<_> = self.samples.iter().collect();
+        samples.sort_by(|a, b| a.0.cmp(b.0));
+        binary::put_u64(&mut out, samples.len() as u64);
+        for (label, samples) in samples {
+            binary::put_str(&mut out, label);
+            binary::put_u64(&mut out, samples.len() as u64);
+            for &ms in samples {
+                binary::put_f64(&mut out, ms);
+            }
+        }
+        let counters = self.sorted_counters();
+        binary::put_u64(&mut out, counters.len() as u64);
+        for (name, value) in counters {
+            binary::put_str(&mut out, &name);
+            binary::put_u64(&mut out, value);
+        }
+        out
+    }
+
+    /// Encodes the timer's accumulated statistics for storage or transmission.
+    ///
+    /// This is the same compact encoding as `report_binary`; decode it with `from_bytes`.
+    ///
+    /// # Returns
+    ///
+    /// Returns the encoded bytes.
+    #[cfg(feature = "binary")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.report_binary()
+    }
+
+    /// Replaces the accumulated statistics, retained samples and counters with an encoded report.
+    ///
+    /// Running timers are untouched. On error, the timer is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - A report produced by `report_binary`.
+    ///
+    /// # Returns
+    ///
+    /// Returns an `InvalidData` error if `bytes` is not a valid report.
+    #[cfg(feature = "binary")]
+    pub fn load_binary(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        let mut reader = binary::Reader::new(bytes)?;
+        let mut stats = HashMap::new();
+        for _ in 0..reader.u64()? {
+            let label = reader.string()?;
+            stats.insert(label, TimerStats::decode(&mut reader)?);
+        }
+        let mut samples = HashMap::new();
+        for _ in 0..reader.u64()? {
+            let label = reader.string()?;
+            let len = reader.len()?;
+            let values = (0..len)
+                .map(|_| reader.f64())
+                .collect::<std::io::Result<_>>()?;
+            samples.insert(label, values);
+        }
+        let mut counters = HashMap::new();
+        for _ in 0..reader.u64()? {
+            let name = reader.string()?;
+            counters.insert(name, AtomicU64::new(reader.u64()?));
+        }
+        reader.finish()?;
+        self.stats = stats;
+        self.samples = samples;
+        #[cfg(feature = "approx-quantiles")]
+        self.quantiles.clear();
+        *self.counters.get_mut().unwrap_or_else(|e| e.into_inner()) = counters;
+        Ok(())
+    }
+
+    /// Returns the value of every counter, sorted by name.
+    fn sorted_counters(&self) -> Vec<(String, u64)> {
+        let counters = self.counters.read().unwrap_or_else(|e| e.into_inner());
+        let mut values: Vec<(String, u64)> = counters
+            .iter()
+            .map(|(name, counter)| (name.clone(), counter.load(Ordering::Relaxed)))
+            .collect();
+        values.sort();
+        values
+    }
+
+    /// Returns the sum of the accumulated totals of all labels in milliseconds.
+    fn grand_total_ms(&self) -> f64 {
+        self.stats.values().map(TimerStats::total_ms).sum()
+    }
+
+    /// Merges the accumulated statistics and counters of another `Timer` into this one.
+    ///
+    /// This aggregates per-worker timers. Statistics of the same label are combined with
+    /// `TimerStats::merge`, so means stay weighted by count. Running timers are not merged.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The timer whose statistics to merge in.
+    pub fn merge<T: BuildHasher>(&mut self, other: &Timer<T>) {
+        for (label, stats) in &other.stats {
+            self.stats.entry(label.clone()).or_default().merge(stats);
+        }
+        #[cfg(feature = "approx-quantiles")]
+        for (label, sketch) in &other.quantiles {
+            self.quantiles
+                .entry(label.clone())
+                .or_default()
+                .merge(sketch);
+        }
+        if self.retain_samples {
+            for (label, samples) in &other.samples {
+                self.samples
+                    .entry(label.clone())
+                    .or_default()
+                    .extend_from_slice(samples);
+            }
+        }
+        for (name, value) in other.sorted_counters() {
+            self.count_by(&name, value);
+        }
+    }
+
+    /// Moves the running timers of another `Timer` into this one.
+    ///
+    /// This hands in-progress measurements over from a sub-task: absorbed timers keep their
+    /// original start, pause state and tags, so ending them here reports the full elapsed
+    /// time. Labels that are already running in this timer are skipped, and `other`'s
+    /// statistics are left alone; use `merge` for those.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The timer whose running timers to take over.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of timers moved.
+    pub fn absorb<T: BuildHasher>(&mut self, mut other: Timer<T>) -> usize {
+        let mut moved = 0;
+        for (label, start_time) in other.timers.drain() {
+            if self.timers.contains_key(&label) {
+                continue;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(paused_at) = other.paused.remove(&label) {
+                self.paused.insert(label.clone(), paused_at);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(paused_for) = other.paused_total.remove(&label) {
+                self.paused_total.insert(label.clone(), paused_for);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(wall_start) = other.wall_starts.remove(&label) {
+                self.wall_starts.insert(label.clone(), wall_start);
+            }
+            #[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+            if let Some(allocs) = other.alloc_starts.remove(&label) {
+                self.alloc_starts.insert(label.clone(), allocs);
+            }
+            if let Some(logged) = other.last_logged.remove(&label) {
+                self.last_logged.insert(label.clone(), logged);
+            }
+            if let Some(location) = other.locations.remove(&label) {
+                self.locations.insert(label.clone(), location);
+            }
+            if let Some(tags) = other.tags.remove(&label) {
+                self.tags.insert(label.clone(), tags);
+            }
+            if let Some(category) = other.categories.remove(&label) {
+                self.categories.insert(label.clone(), category);
+            }
+            if let Some(depth) = other.depths.remove(&label) {
+                self.depths.insert(label.clone(), depth);
+            }
+            self.timers.insert(label, start_time);
+            moved += 1;
+        }
+        self.peak_concurrent = self.peak_concurrent.max(self.timers.len());
+        moved
+    }
+
+    /// Removes every active timer and accumulated statistic in a dotted label subtree.
+    ///
+    /// A label belongs to the subtree of `prefix` if it equals `prefix` or starts with
+    /// `prefix` followed by a `.`, so clearing `db` removes `db.query` but not `dbx`.
+    /// Everything recorded for those labels goes with them, e.g. tags, call sites, parents and
+    /// sampling counters; configuration such as budgets and baselines is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The root of the label subtree to clear.
+    ///
+    /// # Returns
+    ///
+    /// Returns the total number of entries removed from the active timers and the statistics.
+    pub fn clear_prefix(&mut self, prefix: &str) -> usize {
+        let before = self.timers.len() + self.stats.len();
+        self.timers.retain(|label, _| !in_namespace(label, prefix));
+        self.stats.retain(|label, _| !in_namespace(label, prefix));
+        self.samples.retain(|label, _| !in_namespace(label, prefix));
+        #[cfg(feature = "approx-quantiles")]
+        self.quantiles
+            .retain(|label, _| !in_namespace(label, prefix));
+        self.last_logged
+            .retain(|label, _| !in_namespace(label, prefix));
+        self.tags.retain(|label, _| !in_namespace(label, prefix));
+        self.depths.retain(|label, _| !in_namespace(label, prefix));
+        self.locations
+            .retain(|label, _| !in_namespace(label, prefix));
+        self.categories
+            .retain(|label, _| !in_namespace(label, prefix));
+        self.sample_counters
+            .retain(|label, _| !in_namespace(label, prefix));
+        self.parents
+            .retain(|label, parent| !in_namespace(label, prefix) && !in_namespace(parent, prefix));
+        #[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+        {
+            self.alloc_starts
+                .retain(|label, _| !in_namespace(label, prefix));
+            self.allocations
+                .retain(|label, _| !in_namespace(label, prefix));
+        }
+        #[cfg(all(feature = "prometheus", not(target_arch = "wasm32")))]
+        self.exemplars
+            .retain(|label, _| !in_namespace(label, prefix));
+        #[cfg(not(target_arch = "wasm32"))]
+        self.wall_starts
+            .retain(|label, _| !in_namespace(label, prefix));
+        #[cfg(not(target_arch = "wasm32"))]
+        self.paused_total
+            .retain(|label, _| !in_namespace(label, prefix));
+        #[cfg(not(target_arch = "wasm32"))]
+        self.paused.retain(|label, _| !in_namespace(label, prefix));
+        before - self.timers.len() - self.stats.len()
+    }
+
+    /// Enables or disables label usage tracking.
+    ///
+    /// While enabled, calls to `time_end` for labels that were never started are recorded
+    /// so they can be reported by `usage_report`. Disabling tracking clears those records.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to track label usage.
+    pub fn set_track_usage(&mut self, enabled: bool) {
+        self.track_usage = enabled;
+        if !enabled {
+            self.unmatched_ends.clear();
+        }
+    }
+
+    /// Builds a report of instrumentation mistakes seen so far.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `UsageReport` listing the labels that are still running (started but never
+    /// ended) and, if usage tracking is enabled, the labels that were ended without being started.
+    pub fn usage_report(&self) -> UsageReport {
+        let mut leaked: Vec<String> = self.timers.keys().cloned().collect();
+        leaked.sort();
+        UsageReport {
+            leaked,
+            unmatched_ends: self.unmatched_ends.clone(),
+        }
+    }
+
+    /// Sends printed lines to a custom writer instead of stdout.
+    ///
+    /// Lines that are still buffered are flushed to the previous destination first.
+    /// Error messages are always written to stderr.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination for printed lines.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_writer(&mut self, writer: Box<dyn std::io::Write + Send>) {
+        self.output_mut().set_writer(Some(writer));
+    }
+
+    /// Sends the printed lines of a dotted label subtree to their own writer.
+    ///
+    /// A label belongs to the subtree of `prefix` if it equals `prefix` or starts with
+    /// `prefix` followed by a `.`, so `db` routes `db.query` but not `dbx`. When several
+    /// prefixes match, the longest wins; other lines go to the default destination set by
+    /// `set_writer`. Routing a prefix again replaces its writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The root of the label subtree to route.
+    /// * `writer` - The destination for the subtree's lines.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn route_prefix(&mut self, prefix: &str, writer: Box<dyn std::io::Write + Send>) {
+        self.output_mut().route(prefix, false, writer);
+    }
+
+    /// Sends the printed lines of a single label to their own writer, e.g. stderr.
+    ///
+    /// This isolates one timer for focused debugging while every other line stays on the
+    /// default destination. A label sink takes precedence over `route_prefix` routes, and
+    /// unlike them it doesn't cover dotted descendants of the label.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `writer` - The destination for the label's lines.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_label_sink(&mut self, label: &str, writer: Box<dyn std::io::Write + Send>) {
+        let label = self.label_key(label).into_owned();
+        self.output_mut().route(&label, true, writer);
+    }
+
+    /// Restores stdout as the destination for printed lines.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reset_writer(&mut self) {
+        self.output_mut().set_writer(None);
+    }
+
+    /// Enables or disables buffered output.
+    ///
+    /// Printing every line synchronously serializes on the stdout lock, which slows hot paths.
+    /// With buffering enabled, printed lines are collected in memory and written in one batch
+    /// once `threshold` lines are pending, when `flush` is called, or when the timer is dropped.
+    /// Passing `None` disables buffering and writes any pending lines.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The number of pending lines that triggers a flush, or `None` to disable buffering.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_buffer_threshold(&mut self, threshold: Option<usize>) {
+        self.output_mut()
+            .set_threshold(threshold.map(|threshold| threshold.max(1)));
+    }
+
+    /// Writes all buffered lines.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn flush(&self) {
+        self.output().flush();
+    }
+
+    /// Returns the number of buffered lines waiting to be written.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pending_lines(&self) -> usize {
+        self.output().pending()
+    }
+
+    /// Prints a line about a label through the configured output, honoring prefix routes.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn emit(&self, label: &str, line: String) {
+        self.output().emit_for(label, line);
+    }
+
+    /// Locks the output, recovering it if a previous holder panicked.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn output(&self) -> std::sync::MutexGuard<'_, output::Output> {
+        self.output.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the output without locking, since `&mut self` guarantees exclusive access.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn output_mut(&mut self) -> &mut output::Output {
+        self.output.get_mut().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Pushes every completed measurement onto a channel.
+    ///
+    /// Each `time_end` sends the label and the elapsed `Duration`, which lets a separate thread
+    /// aggregate or display measurements. If the receiver is dropped, the failure is logged once
+    /// and the channel is detached.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The sending half of the channel.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_channel(&mut self, tx: Sender<(String, Duration)>) {
+        self.channel = Some(tx);
+    }
+
+    /// Appends every completed timing to a file as newline-delimited JSON.
+    ///
+    /// Each `time_end` writes a line of the form `{"label":"parse","ms":1.234,"ts":1700000000000}`,
+    /// where `ts` is the Unix timestamp in milliseconds at which the timer ended. The file is
+    /// opened in append mode and created if it doesn't exist. Errors are logged, never raised.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file to append to.
+    #[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+    pub fn set_ndjson_sink(&mut self, path: &Path) {
+        match std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+        {
+            Ok(file) => self.ndjson_sink = Some(file),
+            Err(err) => {
+                eprintln!("Failed to open NDJSON sink '{}': {}", path.display(), err);
+                self.ndjson_sink = None;
+            }
+        }
+    }
+
+    /// Writes a completed timing to the NDJSON sink, if one is set.
+    #[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+    fn write_ndjson(&mut self, label: &str, ms: f64) {
+        use std::io::Write;
+
+        if let Some(file) = self.ndjson_sink.as_mut() {
+            let ts = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or(0);
+            let line = format!(
+                "{{\"label\":{},\"ms\":{},\"ts\":{}}}\n",
+                json::string(label),
+                json::number(ms),
+                ts
+            );
+            if let Err(err) = file.write_all(line.as_bytes()).and_then(|_| file.flush()) {
+                eprintln!("Failed to write NDJSON record for '{}': {}", label, err);
+            }
+        }
+    }
+
+    /// Sets whether completed timings are recorded for `report_chrome_trace`.
+    ///
+    /// Recording is off by default because every completed timing is kept in memory.
+    /// Disabling it discards the recorded events.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to record trace events.
+    #[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+    pub fn set_record_trace(&mut self, enabled: bool) {
+        match (enabled, self.trace.is_some()) {
+            (true, false) => self.trace = Some(Vec::new()),
+            (false, _) => self.trace = None,
+            _ => {}
+        }
+    }
+
+    /// Returns the recorded timings in the Chrome tracing JSON format.
+    ///
+    /// Every completed timing is a complete (`"ph":"X"`) event with its start (`ts`) and
+    /// duration (`dur`) in microseconds, so nested timers show up nested in
+    /// `chrome://tracing` or Perfetto. Start timestamps are relative to the first `Timer`
+    /// construction. Only timings completed while `set_record_trace(true)` was on are included.
+    ///
+    /// # Returns
+    ///
+    /// Returns a JSON array of trace events.
+    #[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+    pub fn report_chrome_trace(&self) -> String {
+        let pid = std::process::id();
+        let events: Vec<String> = self
+            .trace
+            .iter()
+            .flatten()
+            .map(|(label, ts, dur, tid)| {
+                format!(
+                    "{{\"name\":{},\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":{},\"tid\":{}}}",
+                    json::string(label),
+                    ts,
+                    dur,
+                    pid,
+                    tid
+                )
+            })
+            .collect();
+        format!("[{}]", events.join(","))
+    }
+
+    /// Returns the time elapsed since the most recent timer was ended.
+    ///
+    /// This measures the gaps between measured regions rather than the regions themselves,
+    /// which helps find where unaccounted time goes.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds since the last successful `time_end`, or `None` if no
+    /// timer has been ended yet.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn since_last_end(&self) -> Option<f64> {
+        let last_end = self.last_end?;
+        let gap = Instant::now().saturating_duration_since(last_end);
+        Some(Timer::duration_to_ms(self.scaled(gap)))
+    }
+
+    /// Attaches a tag to a running timer.
+    ///
+    /// Tags describe a single measurement and are dropped when the timer ends. With the
+    /// `prometheus` feature, a `trace_id` tag turns the measurement into the label's OpenMetrics
+    /// exemplar, linking latency spikes to traces. With the `statsd` feature, tags are sent along
+    /// with the timing.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the running timer.
+    /// * `key` - The tag key, e.g. `trace_id`.
+    /// * `value` - The tag value.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the tag was attached, or `false` if the timer isn't running.
+    pub fn tag(&mut self, label: &str, key: &str, value: &str) -> bool {
+        let label = self.label_key(label).into_owned();
+        if !self.timers.contains_key(&label) {
+            return false;
+        }
+        let tags = self.tags.entry(label).or_default();
+        tags.retain(|(existing, _)| existing != key);
+        tags.push((key.to_string(), value.to_string()));
+        true
+    }
+
+    /// Remembers a completed measurement as its label's exemplar if it carries a `trace_id` tag.
+    #[cfg(all(feature = "prometheus", not(target_arch = "wasm32")))]
+    fn record_exemplar(&mut self, label: &str, ms: f64) {
+        let Some((_, trace_id)) = self
+            .tags
+            .get(label)
+            .and_then(|tags| tags.iter().find(|(key, _)| key == "trace_id"))
+        else {
+            return;
+        };
+        let ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0.0, |d| d.as_secs_f64());
+        let exemplar = (ms, trace_id.clone(), ts);
+        self.exemplars.insert(label.to_string(), exemplar);
+    }
+
+    /// Exports the accumulated statistics as OpenMetrics histograms.
+    ///
+    /// Every label is a series of the `timelog_duration_seconds` histogram. Finite buckets
+    /// are counted from retained samples, so enable `set_retain_samples` to fill them; the
+    /// `+Inf` bucket, sum and count always come from the statistics. The latest measurement
+    /// tagged with a `trace_id` is attached as an exemplar to the first bucket containing it,
+    /// e.g. `# {trace_id="abc"} 0.123 1700000000.5`.
+    ///
+    /// # Arguments
+    ///
+    /// * `buckets_ms` - The bucket upper bounds in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// Returns the exposition text, terminated by `# EOF`.
+    #[cfg(all(feature = "prometheus", not(target_arch = "wasm32")))]
+    pub fn report_openmetrics(&self, buckets_ms: &[f64]) -> String {
+        let metric = prometheus::METRIC;
+        let mut out = format!("# TYPE {} histogram\n# UNIT {} seconds\n", metric, metric);
+        for (label, stats) in self.sorted_stats() {
+            let name = prometheus::escape(label);
+            let mut exemplar = self.exemplars.get(label);
+            for (bound_ms, count) in self.histogram(label, buckets_ms) {
+                let count = if bound_ms.is_infinite() {
+                    stats.count()
+                } else {
+                    count
+                };
+                out.push_str(&format!(
+                    "{}_bucket{{label=\"{}\",le=\"{}\"}} {}",
+                    metric,
+                    name,
+                    prometheus::bound(bound_ms / 1000.0),
+                    count
+                ));
+                if let Some((ms, trace_id, ts)) = exemplar.filter(|(ms, _, _)| *ms <= bound_ms) {
+                    out.push_str(&format!(
+                        " # {{trace_id=\"{}\"}} {} {}",
+                        prometheus::escape(trace_id),
+                        ms / 1000.0,
+                        ts
+                    ));
+                    exemplar = None;
+                }
+                out.push('\n');
+            }
+            out.push_str(&format!(
+                "{}_sum{{label=\"{}\"}} {}\n{}_count{{label=\"{}\"}} {}\n",
+                metric,
+                name,
+                stats.total_ms() / 1000.0,
+                metric,
+                name,
+                stats.count()
+            ));
+        }
+        out.push_str("# EOF\n");
+        out
+    }
+
+    /// Returns the highest number of timers that were running at the same time.
+    ///
+    /// A watermark that keeps growing in a long-running process usually means labels are
+    /// started without being ended.
+    pub fn peak_concurrent(&self) -> usize {
+        self.peak_concurrent
+    }
+
+    /// Resets the concurrency watermark to the number of currently running timers.
+    pub fn reset_peak(&mut self) {
+        self.peak_concurrent = self.timers.len();
+    }
+
+    /// Returns a copy of every running timer's elapsed time, keyed by label.
+    ///
+    /// This is a low-level escape hatch for custom analysis. Elapsed values are computed once,
+    /// at snapshot time, and do not advance afterwards; paused time is excluded and the scale
+    /// is applied like in `time_log`.
+    ///
+    /// # Returns
+    ///
+    /// Returns a map from each running label to its elapsed `Duration`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn snapshot_map(&self) -> HashMap<String, Duration> {
+        let now = Instant::now();
+        self.timers
+            .iter()
+            .map(|(label, &start_time)| {
+                let elapsed = self.scaled(self.running_time(label, start_time, now));
+                (label.clone(), elapsed)
+            })
+            .collect()
+    }
+
+    /// Compares the accumulated statistics against a baseline snapshot and flags regressions.
+    ///
+    /// Only labels present in both the baseline and the current statistics are compared. This
+    /// allows simple performance-regression checks in CI without a benchmarking framework.
+    ///
+    /// # Arguments
+    ///
+    /// * `baseline` - The snapshot to compare against, e.g. from `save_state` on a known-good run.
+    /// * `threshold_pct` - The mean increase in percent above which a label counts as regressed.
+    ///
+    /// # Returns
+    ///
+    /// Returns the regressed labels, sorted by label.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn compare_to_baseline(
+        &self,
+        baseline: &TimerState,
+        threshold_pct: f64,
+    ) -> Vec<Regression> {
+        self.sorted_stats()
+            .into_iter()
+            .filter_map(|(label, stats)| {
+                let baseline_mean_ms = baseline.stats(label)?.mean_ms();
+                if baseline_mean_ms <= 0.0 {
+                    return None;
+                }
+                let current_mean_ms = stats.mean_ms();
+                let change_pct = (current_mean_ms - baseline_mean_ms) / baseline_mean_ms * 100.0;
+                (change_pct > threshold_pct).then(|| Regression {
+                    label: label.to_string(),
+                    baseline_mean_ms,
+                    current_mean_ms,
+                    change_pct,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the time elapsed since the process started, for cold-start analysis.
+    ///
+    /// There is no portable way to get the process start as an `Instant`, so the reference
+    /// point is the first time the crate was touched: the first `Timer` construction or the
+    /// first call to this method, whichever came first. Construct a `Timer` early in `main`
+    /// to make the value approximate the process lifetime.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds since the reference point.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn since_process_start(&self) -> f64 {
+        if !ENABLED {
+            return 0.0;
+        }
+        Timer::duration_to_ms(PROCESS_START.get_or_init(Instant::now).elapsed())
+    }
+
+    /// Captures a snapshot of the timer's state.
+    ///
+    /// The snapshot includes running and paused timers, accumulated statistics, retained
+    /// samples and counters. Configuration such as budgets or writers is not included.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `TimerState` that can later be passed to `restore_state`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_state(&self) -> TimerState {
+        TimerState {
+            timers: self
+                .timers
+                .iter()
+                .map(|(label, &start_time)| (label.clone(), start_time))
+                .collect(),
+            paused: self.paused.clone(),
+            paused_total: self.paused_total.clone(),
+            wall_starts: self.wall_starts.clone(),
+            depths: self.depths.clone(),
+            tags: self.tags.clone(),
+            locations: self.locations.clone(),
+            categories: self.categories.clone(),
+            last_logged: self.last_logged.clone(),
+            #[cfg(feature = "alloc-count")]
+            alloc_starts: self.alloc_starts.clone(),
+            stats: self.stats.clone(),
+            samples: self.samples.clone(),
+            #[cfg(feature = "approx-quantiles")]
+            quantiles: self.quantiles.clone(),
+            counters: self.sorted_counters().into_iter().collect(),
+        }
+    }
+
+    /// Replaces the timer's state with a snapshot.
+    ///
+    /// Restored timers keep their original start instants, so a timer that was running when
+    /// the snapshot was taken reports the time elapsed since it was originally started,
+    /// including the time between saving and restoring.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The snapshot to restore.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn restore_state(&mut self, state: TimerState) {
+        self.timers.clear();
+        self.timers.extend(state.timers);
+        self.paused = state.paused;
+        self.paused_total = state.paused_total;
+        self.wall_starts = state.wall_starts;
+        self.depths = state.depths;
+        self.tags = state.tags;
+        self.locations = state.locations;
+        self.categories = state.categories;
+        self.last_logged = state.last_logged;
+        #[cfg(feature = "alloc-count")]
+        {
+            self.alloc_starts = state.alloc_starts;
+        }
+        self.stats = state.stats;
+        self.samples = state.samples;
+        #[cfg(feature = "approx-quantiles")]
+        {
+            self.quantiles = state.quantiles;
+        }
+        *self.counters.get_mut().unwrap_or_else(|e| e.into_inner()) = state
+            .counters
+            .into_iter()
+            .map(|(name, value)| (name, AtomicU64::new(value)))
+            .collect();
+    }
+}
+
+/// Quotes a logfmt value if it contains spaces, `=` or quotes.
+fn logfmt_value(value: &str) -> Cow<'_, str> {
+    if !value.is_empty() && !value.contains([' ', '=', '"']) {
+        return Cow::Borrowed(value);
+    }
+    Cow::Owned(format!("\"{}\"", value.replace('"', "\\\"")))
+}
+
+/// Returns whether `label` is `prefix` itself or a dotted descendant of it.
+fn in_namespace(label: &str, prefix: &str) -> bool {
+    match label.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('.') || prefix.ends_with('.'),
+        None => false,
+    }
+}
+
+/// Returns `part` as a percentage of `total`, or 0.0 if `total` is zero.
+fn percent_of(part: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        part / total * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Returns a small, stable id for the current thread, used as the `tid` of trace events.
+#[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+fn trace_thread_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static THREAD_ID: u64 = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    }
+    THREAD_ID.with(|id| *id)
+}
+
+/// Flushes buffered output when the `Timer` is dropped.
+#[cfg(not(target_arch = "wasm32"))]
+impl<S> Drop for Timer<S> {
+    fn drop(&mut self) {
+        self.output
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .flush();
+    }
+}
+
+/// Implements the `Default` trait for `Timer`.
+impl Default for Timer {
+    /// Creates a default `Timer` instance.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `Timer` instance.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Test module
+#[cfg(all(test, not(feature = "disabled")))]
+mod tests {
+    use super::*;
+    #[cfg(not(target_arch = "wasm32"))]
+    use std::thread::sleep;
+    #[cfg(not(target_arch = "wasm32"))]
+    use std::time::Duration;
+
+    /// Converts milliseconds to a `Duration`, rounding to the nearest nanosecond
+    fn from_ms(ms: f64) -> Duration {
+        Duration::from_nanos((ms * 1_000_000.0).round() as u64)
+    }
+
+    /// A writer that appends into a shared buffer, for capturing printed lines
+    #[cfg(not(target_arch = "wasm32"))]
+    #[derive(Clone, Default)]
+    struct SharedBuf(std::sync::Arc<Mutex<Vec<u8>>>);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl SharedBuf {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl std::io::Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Tests Timer::new() and Timer::default()
+    #[test]
+    fn test_timer_new() {
+        let timer = Timer::default();
+        assert!(timer.timers.is_empty());
+    }
+
+    /// Tests Timer::with_capacity() method
+    #[test]
+    fn test_timer_with_capacity() {
+        let mut timer = Timer::with_capacity(256);
+        assert!(timer.timers.capacity() >= 256);
+        for i in 0..256 {
+            timer.time(&format!("timer_{}", i));
+        }
+        assert_eq!(timer.timers.len(), 256);
+    }
+
+    /// Tests Timer::with_started() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_with_started() {
+        let mut timer = Timer::with_started(["fetch", "parse", "render"]);
+        assert_eq!(timer.timers.len(), 3);
+        assert_eq!(timer.timers["fetch"], timer.timers["render"]);
+        for label in ["fetch", "parse", "render"] {
+            assert_eq!(timer.is_paused(label), Some(false));
+        }
+        assert!(timer.time_end("parse", true) >= 0.0);
+        assert_eq!(timer.peak_concurrent(), 3);
+        #[cfg(feature = "alloc-count")]
+        assert_eq!(timer.alloc_starts.len(), 2);
+    }
+
+    /// Tests Timer::with_hasher() method
+    #[test]
+    fn test_timer_with_hasher() {
+        /// A minimal FNV-1a hasher standing in for a fast non-SipHash hasher.
+        #[derive(Default)]
+        struct FnvHasher(u64);
+
+        impl std::hash::Hasher for FnvHasher {
+            fn finish(&self) -> u64 {
+                self.0
+            }
+
+            fn write(&mut self, bytes: &[u8]) {
+                for &byte in bytes {
+                    self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x100000001b3);
+                }
+            }
+        }
+
+        let hasher = std::hash::BuildHasherDefault::<FnvHasher>::default();
+        let mut timer = Timer::with_hasher(hasher);
+        timer.time("fast");
+        timer.time_many(&["a", "b"]);
+        assert!(timer.time_log("fast", true) >= 0.0);
+        assert!(timer.time_end("fast", true) >= 0.0);
+        assert_eq!(timer.time_end_many(&["a", "b"]).len(), 2);
+        assert_eq!(timer.stats("fast").unwrap().count(), 1);
+        {
+            let _scope = timer.scope("scoped");
+        }
+        assert_eq!(timer.stats("scoped").unwrap().count(), 1);
+        assert!(timer.timers.is_empty());
+
+        let mut merged = Timer::new();
+        merged.merge(&timer);
+        assert_eq!(merged.stats("scoped").unwrap().count(), 1);
+    }
+
+    /// Tests Timer::time() method
+    #[test]
+    fn test_timer_time() {
+        let mut timer = Timer::new();
+        timer.time("test");
+        assert!(timer.timers.contains_key("test"));
+    }
+
+    /// Tests Timer::time_log() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_log() {
+        let mut timer = Timer::new();
+        timer.time("test_time_log");
+        sleep(Duration::from_millis(10));
+        let ms = timer.time_log("test_time_log", false);
+        assert!(ms > 10.0 && ms < 15.0);
+    }
+
+    /// Tests Timer::time_every_n() method
+    #[test]
+    fn test_timer_time_every_n() {
+        let mut timer = Timer::new();
+        let sampled = (0..10)
+            .filter(|_| timer.time_every_n("test_every_n", 3))
+            .count();
+        assert_eq!(sampled, 3);
+        assert!(timer.timers.contains_key("test_every_n"));
+    }
+
+    /// Tests Timer::time_log_delta() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_log_delta() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        timer.time("loop");
+        sleep(Duration::from_millis(10));
+        let (total, delta) = timer.time_log_delta("loop");
+        assert!(total >= 10.0);
+        assert_eq!(total, delta);
+        sleep(Duration::from_millis(20));
+        let (second_total, second_delta) = timer.time_log_delta("loop");
+        assert!((20.0..30.0).contains(&second_delta));
+        assert!((second_total - total - second_delta).abs() < 1e-9);
+        assert!(buf.contents().starts_with("loop: total="));
+        assert!(buf.contents().contains("ms (+"));
+        assert_eq!(timer.time_log_delta("missing"), (0.0, 0.0));
+    }
+
+    /// Tests Timer::time_log_with() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_log_with() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        timer.time("load");
+
+        let called = std::cell::Cell::new(false);
+        timer.time_log_with("load", true, || {
+            called.set(true);
+            "silenced".to_string()
+        });
+        assert!(!called.get());
+        assert_eq!(buf.contents(), "");
+
+        timer.time_log_with("load", false, || format!("{} rows", 42));
+        assert!(buf.contents().starts_with("load: "));
+        assert!(buf.contents().ends_with("ms 42 rows\n"));
+    }
+
+    /// Tests Timer::event() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_event() {
+        let mut timer = Timer::new();
+        timer.time("test_event");
+        sleep(Duration::from_millis(5));
+        let offset = timer.event("test_event", "checkpoint");
+        assert!(offset >= 5.0);
+        assert!(timer.timers.contains_key("test_event"));
+        assert_eq!(timer.event("missing", "checkpoint"), 0.0);
+    }
+
+    /// Tests Timer::time_end() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_end() {
+        let mut timer = Timer::new();
+        timer.time("test_time_end");
+        sleep(Duration::from_millis(10));
+        timer.time_end("test_time_end", false);
+        assert!(!timer.timers.contains_key("test"));
+    }
+
+    /// Tests Timer::time_many() and Timer::time_end_many() methods
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_many() {
+        let mut timer = Timer::new();
+        timer.time_many(&["a", "b"]);
+        assert!(timer.timers.contains_key("a") && timer.timers.contains_key("b"));
+        let results = timer.time_end_many(&["a", "missing", "b"]);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[1], 0.0);
+        assert!(timer.timers.is_empty());
+    }
+
+    /// Tests Timer::usage_report() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_usage_report() {
+        let mut timer = Timer::new();
+        timer.set_track_usage(true);
+        timer.time("leaked");
+        timer.time("matched");
+        timer.time_end("matched", true);
+        timer.time_end("never_started", true);
+        let report = timer.usage_report();
+        assert_eq!(report.leaked, vec!["leaked".to_string()]);
+        assert_eq!(report.unmatched_ends, vec!["never_started".to_string()]);
+        assert!(!report.is_clean());
+    }
+
+    /// Tests Timer::set_ndjson_sink() method
+    #[test]
+    #[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+    fn test_timer_ndjson_sink() {
+        let path =
+            std::env::temp_dir().join(format!("timelog_ndjson_{}.ndjson", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut timer = Timer::new();
+        timer.set_ndjson_sink(&path);
+        timer.time("first");
+        timer.time_end("first", true);
+        timer.time("say \"hi\"");
+        timer.time_end("say \"hi\"", true);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("{\"label\":\"first\",\"ms\":"));
+        assert!(lines[1].starts_with("{\"label\":\"say \\\"hi\\\"\",\"ms\":"));
+        assert!(lines[1].contains(",\"ts\":"));
+    }
+
+    /// Tests that Timer::time_end() accumulates statistics
+    #[test]
+    fn test_timer_stats() {
+        let mut timer = Timer::new();
+        assert!(timer.stats("test_stats").is_none());
+        timer.time("test_stats");
+        timer.time_end("test_stats", true);
+        timer.time("test_stats");
+        timer.time_end("test_stats", true);
+        let stats = timer.stats("test_stats").unwrap();
+        assert_eq!(stats.count(), 2);
+        assert!(stats.min_ms() <= stats.mean_ms() && stats.mean_ms() <= stats.max_ms());
+    }
+
+    /// Tests Timer::bench_with_warmup() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_bench_with_warmup() {
+        let mut timer = Timer::new();
+        let mut calls = 0;
+        let stats = timer.bench_with_warmup("test_bench", 3, 5, || calls += 1);
+        assert_eq!(calls, 8);
+        assert_eq!(stats.count(), 5);
+        assert_eq!(timer.stats("test_bench").unwrap().count(), 5);
+    }
+
+    /// Tests Timer::count(), Timer::count_by() and Timer::counter() methods
+    #[test]
+    fn test_timer_counters() {
+        let timer = Timer::new();
+        assert_eq!(timer.counter("hits"), None);
+        timer.count("hits");
+        timer.count_by("hits", 4);
+        assert_eq!(timer.counter("hits"), Some(5));
+        assert!(timer.timers.is_empty());
+    }
+
+    /// Tests Timer::report() method
+    #[test]
+    fn test_timer_report() {
+        let mut timer = Timer::new();
+        timer.time("parse");
+        timer.time_end("parse", true);
+        timer.count_by("hits", 3);
+        let report = timer.report();
+        assert!(report.starts_with("Timers:\n  parse: 1 calls, total "));
+        assert!(report.ends_with("Counters:\n  hits: 3\n"));
+    }
+
+    /// Tests the budget suffix of Timer::time_end() output
+    #[test]
+    fn test_timer_budget_message() {
+        let mut timer = Timer::new();
+        assert_eq!(timer.end_message("parse", 36.5), "parse: 36.500ms");
+        timer.set_budget("parse", 50.0);
+        assert_eq!(
+            timer.end_message("parse", 36.5),
+            "parse: 36.500ms (73% of 50ms budget)"
+        );
+    }
+
+    /// Tests Timer::set_budgets() and Timer::budget() methods
+    #[test]
+    fn test_timer_set_budgets() {
+        let mut timer = Timer::new();
+        timer.set_budget("render", 16.0);
+        timer.set_budgets(HashMap::from([
+            ("parse".to_string(), 50.0),
+            ("query".to_string(), 200.0),
+        ]));
+        assert_eq!(timer.budget("parse"), Some(50.0));
+        assert_eq!(timer.budget("render"), Some(16.0));
+        assert_eq!(timer.budget("unknown"), None);
+        assert_eq!(
+            timer.end_message("parse", 60.0),
+            "parse: 60.000ms (120% of 50ms budget, over by 10.000ms)"
+        );
+        assert_eq!(
+            timer.end_message("query", 50.0),
+            "query: 50.000ms (25% of 200ms budget)"
+        );
+    }
+
+    /// Tests Timer::remaining_ms() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_remaining_ms() {
+        let mut timer = Timer::new();
+        timer.time("task");
+        assert_eq!(timer.remaining_ms("task"), None);
+        assert_eq!(timer.remaining_ms("missing"), None);
+
+        timer.set_budget("task", 1000.0);
+        let remaining = timer.remaining_ms("task").unwrap();
+        assert!(remaining > 900.0 && remaining <= 1000.0);
+
+        timer.set_budget("task", 0.0);
+        let remaining = timer.remaining_ms("task").unwrap();
+        assert!(remaining <= 0.0 && remaining > -100.0);
+
+        timer.time_at("late", Instant::now() - Duration::from_millis(100));
+        timer.set_budget("late", 50.0);
+        assert!(timer.remaining_ms("late").unwrap() <= -50.0);
+        timer.time_end("late", true);
+        assert_eq!(timer.remaining_ms("late"), None);
+    }
+
+    /// Tests Timer::percentile() method
+    #[test]
+    #[cfg(feature = "approx-quantiles")]
+    fn test_timer_percentile() {
+        let mut timer = Timer::new();
+        assert_eq!(timer.percentile("op", 99.0), None);
+        for i in 1..=100_000 {
+            timer.record("op", from_ms(i as f64 / 100.0));
+        }
+        let p99 = timer.percentile("op", 99.0).unwrap();
+        assert!((p99 - 990.0).abs() / 990.0 < 0.02, "p99 = {}", p99);
+        let p50 = timer.percentile("op", 50.0).unwrap();
+        assert!((p50 - 500.0).abs() / 500.0 < 0.02, "p50 = {}", p50);
+        assert!(timer.percentile("op", 0.0).unwrap() <= 0.0101);
+    }
+
+    /// Tests Timer::set_baseline() method
+    #[test]
+    fn test_timer_baseline_message() {
+        let mut timer = Timer::new();
+        timer.set_baseline("db_query", 40.0);
+        assert_eq!(
+            timer.end_message("db_query", 52.0),
+            "db_query: 52.000ms (1.3x baseline 40ms)"
+        );
+        timer.set_budget("db_query", 100.0);
+        assert_eq!(
+            timer.end_message("db_query", 20.0),
+            "db_query: 20.000ms (20% of 100ms budget) (0.5x baseline 40ms)"
+        );
+    }
+
+    /// Tests Timer::report_binary() and Timer::load_binary() methods
+    #[test]
+    #[cfg(feature = "binary")]
+    fn test_timer_binary_round_trip() {
+        let mut timer = Timer::new();
+        timer.set_retain_samples(true);
+        for ms in [1.5, 2.5, 4.0] {
+            timer.record("parse", from_ms(ms));
+        }
+        timer.record("db.query", from_ms(12.25));
+        timer.count_by("cache_miss", 3);
+        let bytes = timer.report_binary();
+
+        let mut loaded = Timer::new();
+        loaded.load_binary(&bytes).unwrap();
+        assert_eq!(loaded.stats("parse"), timer.stats("parse"));
+        assert_eq!(loaded.stats("db.query"), timer.stats("db.query"));
+        assert_eq!(loaded.samples["parse"], [1.5, 2.5, 4.0]);
+        assert_eq!(loaded.sorted_counters(), [("cache_miss".to_string(), 3)]);
+        assert_eq!(loaded.report_binary(), bytes);
+
+        assert!(loaded.load_binary(&bytes[..bytes.len() - 1]).is_err());
+        assert!(loaded.load_binary(b"{}").is_err());
+        assert_eq!(loaded.stats("parse"), timer.stats("parse"));
+    }
+
+    /// Tests that TimerStats sums many short measurements without drift
+    #[test]
+    fn test_timer_stats_exact_total() {
+        let mut stats = TimerStats::default();
+        for _ in 0..1_000_000 {
+            stats.record(Duration::from_nanos(1));
+        }
+        assert_eq!(stats.total_ns(), 1_000_000);
+        assert_eq!(stats.total_ms(), 1.0);
+        assert_eq!(stats.mean_ms(), 0.000_001);
+
+        // Ten million seconds plus one nanosecond has no exact f64 millisecond value.
+        let long = Duration::new(10_000_000, 1);
+        assert_ne!(
+            (Timer::duration_to_ms(long) * 1_000_000.0) as u128,
+            long.as_nanos()
+        );
+        let mut stats = TimerStats::default();
+        for _ in 0..3 {
+            stats.record(long);
+        }
+        stats.record(Duration::from_nanos(333_333_333));
+        assert_eq!(stats.total_ns(), 30_000_000_333_333_336);
+    }
+
+    /// Tests Timer::all_stats() method
+    #[test]
+    fn test_timer_all_stats() {
+        let mut timer = Timer::new();
+        assert!(timer.all_stats().is_empty());
+        for (label, runs) in [("parse", 3), ("db.query", 1), ("render", 2)] {
+            for _ in 0..runs {
+                timer.record(label, from_ms(1.0));
+            }
+        }
+        let counts: Vec<(String, u64)> = timer
+            .all_stats()
+            .into_iter()
+            .map(|(label, stats)| (label, stats.count()))
+            .collect();
+        assert_eq!(
+            counts,
+            [
+                ("db.query".to_string(), 1),
+                ("parse".to_string(), 3),
+                ("render".to_string(), 2)
+            ]
+        );
+    }
+
+    /// Tests Millis::display_as() method
+    #[test]
+    fn test_millis_display_as() {
+        let elapsed = Millis(1.5);
+        assert_eq!(elapsed.display_as(TimeUnit::Micros), "1500.000µs");
+        assert_eq!(elapsed.display_as(TimeUnit::Nanos), "1500000.000ns");
+        assert_eq!(elapsed.display_as(TimeUnit::Millis), "1.500ms");
+        assert_eq!(elapsed.display_as(TimeUnit::Secs), "0.002s");
+
+        let mut timer = Timer::new();
+        assert_eq!(
+            timer
+                .time_end_typed("missing", true)
+                .display_as(TimeUnit::Micros),
+            "0.000µs"
+        );
+    }
+
+    /// Tests Timer::begin_frame() and Timer::end_frame() methods
+    #[test]
+    fn test_timer_frames() {
+        let mut timer = Timer::new();
+        timer.record("before", from_ms(1.0));
+        timer.begin_frame();
+        timer.record("physics", from_ms(2.0));
+        timer.record("render", from_ms(5.0));
+        timer.record("physics", from_ms(1.5));
+        let first = timer.end_frame();
+        timer.record("between", from_ms(1.0));
+        timer.begin_frame();
+        timer.record("render", from_ms(4.0));
+        let second = timer.end_frame();
+
+        assert_eq!(first.frame, 0);
+        assert_eq!(first.timings.len(), 3);
+        assert_eq!(first.total_ms("physics"), Some(3.5));
+        assert_eq!(first.total_ms("render"), Some(5.0));
+        assert_eq!(second.frame, 1);
+        assert_eq!(second.timings, [("render".to_string(), 4.0)]);
+        assert_eq!(second.total_ms("physics"), None);
+        assert_eq!(timer.stats("render").unwrap().count(), 2);
+    }
+
+    /// Tests Timer::samples() method
+    #[test]
+    fn test_timer_samples() {
+        let mut timer = Timer::new();
+        timer.record("parse", from_ms(1.0));
+        assert_eq!(timer.samples("parse"), None);
+
+        timer.set_retain_samples(true);
+        for ms in [3.0, 1.5, 2.25] {
+            timer.record("parse", from_ms(ms));
+        }
+        assert_eq!(timer.samples("parse"), Some(vec![1.5, 2.25, 3.0]));
+        assert_eq!(timer.samples("missing"), None);
+    }
+
+    /// Tests that Timer::print_summary() lists counters next to timers
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_print_summary_counters() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        timer.record("request", from_ms(5.0));
+        timer.count("retries");
+        timer.count_by("retries", 2);
+        timer.count_by("bytes", 1024);
+        timer.count_by("retries", 4);
+        timer.print_summary();
+        let out = buf.contents();
+        assert!(out.starts_with("Timers:\n  request: 1 calls"), "{}", out);
+        assert!(
+            out.ends_with("Counters:\n  bytes: 1024\n  retries: 7\n"),
+            "{}",
+            out
+        );
+    }
+
+    /// Tests Timer::report_if_over() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_report_if_over() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        assert!(!timer.report_if_over(0.0));
+        timer.record("request", from_ms(40.0));
+        timer.record("parse", from_ms(30.0));
+        assert!(!timer.report_if_over(50.0));
+        assert_eq!(buf.contents(), "");
+
+        timer.record("request", from_ms(20.0));
+        assert!(timer.report_if_over(50.0));
+        assert!(
+            buf.contents().starts_with("Timers:\n  parse: "),
+            "{}",
+            buf.contents()
+        );
+    }
+
+    /// Tests Timer::to_bytes() and Timer::from_bytes() methods
+    #[test]
+    #[cfg(feature = "binary")]
+    fn test_timer_bytes_round_trip() {
+        let mut timer = Timer::new();
+        for i in 0..100 {
+            timer.record(&format!("label_{}", i % 7), from_ms(i as f64 * 0.25));
+        }
+        let decoded = Timer::from_bytes(&timer.to_bytes()).unwrap();
+        assert_eq!(decoded.all_stats(), timer.all_stats());
+        assert!(Timer::from_bytes(&[]).is_err());
+    }
+
+    /// Tests Timer::load_best(), Timer::best_ms() and Timer::save_best() methods
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_best_times() {
+        let path = std::env::temp_dir().join(format!("timelog_best_{}.txt", std::process::id()));
+        std::fs::write(&path, "12.5 parse\n30 render\n").unwrap();
+
+        let mut timer = Timer::new();
+        timer.load_best(&path).unwrap();
+        timer.record("parse", from_ms(10.0));
+        timer.record("render", from_ms(45.0));
+        timer.record("new", from_ms(3.0));
+        assert_eq!(timer.best_ms("parse"), Some(10.0));
+        assert!(timer.is_new_best("parse"));
+        assert_eq!(timer.best_ms("render"), Some(30.0));
+        assert!(!timer.is_new_best("render"));
+        assert!(timer.is_new_best("new"));
+        assert_eq!(timer.best_ms("missing"), None);
+
+        timer.save_best(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "3 new\n10 parse\n30 render\n"
+        );
+        std::fs::write(&path, "fast parse\n").unwrap();
+        assert!(Timer::new().load_best(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Tests Timer::set_thresholds() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_thresholds() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        assert_eq!(timer.severity(5.0), None);
+
+        timer.set_thresholds(10.0, 100.0);
+        assert_eq!(timer.severity(5.0), Some(Severity::Ok));
+        assert_eq!(timer.severity(10.0), Some(Severity::Warn));
+        assert_eq!(timer.severity(250.0), Some(Severity::Error));
+        assert_eq!(timer.end_message("parse", 5.0), "[OK] parse: 5.000ms");
+        assert_eq!(timer.end_message("parse", 50.0), "[WARN] parse: 50.000ms");
+        assert_eq!(
+            timer.end_message("parse", 250.0),
+            "[ERROR] parse: 250.000ms"
+        );
+
+        timer.time("fast");
+        timer.time_end("fast", false);
+        assert!(buf.contents().starts_with("[OK] fast: "));
+
+        timer.clear_thresholds();
+        assert_eq!(timer.end_message("parse", 250.0), "parse: 250.000ms");
+    }
+
+    /// Tests Timer::set_thousands_separator() and Timer::set_decimal_separator() methods
+    #[test]
+    #[cfg(feature = "num-format")]
+    fn test_timer_number_separators() {
+        let mut timer = Timer::new();
+        assert_eq!(timer.format_ms(1234567.891), "1234567.891");
+
+        timer.set_thousands_separator(Some(','));
+        assert_eq!(timer.format_ms(1234567.891), "1,234,567.891");
+        assert_eq!(timer.format_ms(-1234.5), "-1,234.500");
+        assert_eq!(timer.format_ms(999.0), "999.000");
+        assert_eq!(timer.end_message("load", 1234.567), "load: 1,234.567ms");
+
+        timer.set_thousands_separator(Some('.'));
+        timer.set_decimal_separator(',');
+        assert_eq!(timer.format_ms(1234.567), "1.234,567");
+    }
+
+    /// Tests that thousands separators reach human-facing output only
+    #[test]
+    #[cfg(feature = "num-format")]
+    fn test_timer_thousands_separator_output() {
+        let mut timer = Timer::new();
+        timer.set_thousands_separator(Some(' '));
+        assert_eq!(timer.format_ms(123456789.0), "123 456 789.000");
+        timer.record("job", from_ms(1234567.5));
+        assert!(timer
+            .report()
+            .contains("  job: 1 calls, total 1 234 567.500ms"));
+        assert_eq!(
+            timer.logfmt_line("job", 1234567.5, None, &[]),
+            "label=job duration_ms=1234567.500"
+        );
+    }
+
+    /// Tests Timer::with_statsd() method
+    #[test]
+    #[cfg(all(feature = "statsd", not(target_arch = "wasm32")))]
+    fn test_timer_with_statsd() {
+        let agent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        agent
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut timer = Timer::with_statsd(&agent.local_addr().unwrap().to_string(), "app");
+        timer.time("parse");
+        let ms = timer.time_end("parse", true);
+        let mut buf = [0u8; 512];
+        let len = agent.recv(&mut buf).unwrap();
+        let line = std::str::from_utf8(&buf[..len]).unwrap();
+        let (name, rest) = line.split_once(':').unwrap();
+        assert_eq!(name, "app.parse");
+        let value = rest.strip_suffix("|ms").unwrap();
+        assert_eq!(value.parse::<f64>().unwrap(), ms);
+
+        timer.time("query");
+        timer.tag("query", "env", "prod");
+        timer.tag("query", "region", "eu");
+        let ms = timer.time_end("query", true);
+        let len = agent.recv(&mut buf).unwrap();
+        let line = std::str::from_utf8(&buf[..len]).unwrap();
+        assert_eq!(line, format!("app.query:{}|ms|#env:prod,region:eu", ms));
+        assert!(timer.tags.is_empty());
+
+        timer.time("db:read|#1");
+        timer.tag("db:read|#1", "host:port", "a,b");
+        let ms = timer.time_end("db:read|#1", true);
+        let len = agent.recv(&mut buf).unwrap();
+        let line = std::str::from_utf8(&buf[..len]).unwrap();
+        assert_eq!(line, format!("app.db_read__1:{}|ms|#host_port:a_b", ms));
+    }
+
+    /// Tests the time_here! macro
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_here() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        let line = line!() + 1;
+        crate::time_here!(timer, "parse");
+        timer.time_end("parse", false);
+        let expected = format!("parse ({}:{}): ", file!(), line);
+        assert!(buf.contents().starts_with(&expected));
+        assert!(timer.locations.is_empty());
+
+        timer.time("parse");
+        assert_eq!(timer.end_message("parse", 1.0), "parse: 1.000ms");
+    }
+
+    /// Tests Timer::time_auto() method
+    #[test]
+    fn test_timer_time_auto() {
+        let mut timer = Timer::new();
+        let first = timer.time_auto();
+        let second = timer.time_auto();
+        assert_eq!(first, "timer_0");
+        assert_eq!(second, "timer_1");
+        assert!(timer.timers.contains_key(&first));
+        assert!(timer.time_end(&second, true) >= 0.0);
+
+        let shared = SharedTimer::new();
+        assert_ne!(shared.time_auto(), shared.clone().time_auto());
+    }
+
+    /// Tests Timer::time_at() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_at() {
+        let mut timer = Timer::new();
+        let start = Instant::now();
+        sleep(Duration::from_millis(10));
+        timer.time_at("handler", start);
+        assert_eq!(timer.timers["handler"], start);
+        assert!(timer.time_end("handler", true) >= 10.0);
+
+        timer.time_at("replay", start);
+        let ms = timer.time_end_at("replay", start + Duration::from_millis(7), true);
+        assert!((ms - 7.0).abs() < 1e-9);
+    }
+
+    /// Tests Timer::time_end_with_stats() method
+    #[test]
+    fn test_timer_time_end_with_stats() {
+        let mut timer = Timer::new();
+        timer.time("step");
+        let (first_ms, stats) = timer.time_end_with_stats("step");
+        assert_eq!(stats.count(), 1);
+        assert_eq!(stats.max_ms(), first_ms);
+
+        timer.time("step");
+        let (second_ms, stats) = timer.time_end_with_stats("step");
+        assert_eq!(stats.count(), 2);
+        let total = from_ms(first_ms) + from_ms(second_ms);
+        assert_eq!(stats.total_ns(), total.as_nanos());
+        assert_eq!(stats.total_ms(), total.as_nanos() as f64 / 1_000_000.0);
+
+        let (ms, stats) = timer.time_end_with_stats("missing");
+        assert_eq!(ms, 0.0);
+        assert_eq!(stats, TimerStats::default());
+    }
+
+    /// Tests Timer::time_end_typed() method and the Millis type
+    #[test]
+    fn test_timer_typed_millis() {
+        let mut timer = Timer::new();
+        timer.time("typed");
+        let logged = timer.time_log_typed("typed", true);
+        let ended = timer.time_end_typed("typed", true);
+        assert!(ended >= logged);
+        assert!(*ended >= 0.0);
+        assert_eq!(timer.time_end_typed("typed", true), Millis(0.0));
+
+        let total = Millis(1.5) + Millis::from(2.0);
+        assert_eq!(f64::from(total), 3.5);
+        assert_eq!(total - Millis(0.5), Millis(3.0));
+        assert_eq!(total.as_secs(), 0.0035);
+        assert_eq!(total.to_string(), "3.500ms");
+        assert_eq!(format!("{:.1}", total), "3.5ms");
+        assert!(total.max(1.0) == 3.5);
+    }
+
+    /// Tests Timer::time_if() and Timer::time_end_if() methods
+    #[test]
+    fn test_timer_time_if() {
+        let mut timer = Timer::new();
+        timer.time_if("sampled", false);
+        assert!(timer.timers.is_empty());
+        assert_eq!(timer.time_end_if("sampled", false), 0.0);
+        assert!(timer.stats("sampled").is_none());
+
+        timer.time_if("sampled", true);
+        assert!(timer.timers.contains_key("sampled"));
+        timer.time_end_if("sampled", true);
+        assert_eq!(timer.stats("sampled").unwrap().count(), 1);
+    }
+
+    /// Tests Timer::time_end_at() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_end_at() {
+        let mut timer = Timer::new();
+        let start = Instant::now();
+        timer.timers.insert("replay".to_string(), start);
+        let ms = timer.time_end_at("replay", start + Duration::from_millis(25), true);
+        assert_eq!(ms, 25.0);
+        timer
+            .timers
+            .insert("backwards".to_string(), start + Duration::from_millis(5));
+        assert_eq!(timer.time_end_at("backwards", start, true), 0.0);
+    }
+
+    /// Tests nested Timer::scope() guards
+    #[test]
+    fn test_timer_nested_scopes() {
+        let mut timer = Timer::new();
+        {
+            let mut parent = timer.scope("parent");
+            let child = parent.scope("child");
+            assert_eq!(child.label(), "child");
+        }
+        assert_eq!(timer.parent("child"), Some("parent"));
+        assert_eq!(timer.parent("parent"), None);
+        assert!(timer.timers.is_empty());
+        assert_eq!(timer.stats("child").unwrap().count(), 1);
+        assert_eq!(timer.stats("parent").unwrap().count(), 1);
+        assert!(timer.scopes.is_empty());
+    }
+
+    /// Tests that Timer::scope() only tracks scopes whose timer was started
+    #[test]
+    fn test_timer_scope_stack() {
+        let mut timer = Timer::new();
+        timer.set_max_label_len(Some(8), LabelLengthPolicy::Reject);
+        timer.set_label_normalizer(Box::new(|label| label.to_lowercase()));
+        {
+            let mut outer = timer.scope("Outer");
+            assert_eq!(outer.scopes, ["outer"]);
+            {
+                let mut rejected = outer.scope("far_too_long_label");
+                assert_eq!(rejected.scopes, ["outer"]);
+                let inner = rejected.scope("Inner");
+                assert_eq!(inner.scopes, ["outer", "inner"]);
+            }
+            assert_eq!(outer.scopes, ["outer"]);
+        }
+        assert!(timer.scopes.is_empty());
+        assert_eq!(timer.parent("inner"), Some("outer"));
+        assert_eq!(timer.parent("far_too_long_label"), None);
+    }
+
+    /// Tests Timer::scope_within() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_scope_within() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        timer.time("request");
+        sleep(Duration::from_millis(10));
+        {
+            let _child = timer.scope_within("parse", "request");
+            sleep(Duration::from_millis(10));
+        }
+        let output = buf.contents();
+        assert!(output.starts_with("parse: "));
+        assert!(output.contains("% of request)\n"));
+        assert_eq!(timer.parent("parse"), Some("request"));
+        assert!(timer.timers.contains_key("request"));
+        assert_eq!(timer.stats("parse").unwrap().count(), 1);
+
+        timer.set_label_normalizer(Box::new(|label: &str| format!("{}/", label)));
+        timer.time("job");
+        drop(timer.scope_within("step", "job"));
+        assert_eq!(timer.stats("step").unwrap().count(), 1);
+        assert!(!timer.timers.contains_key("step/"));
+    }
+
+    /// Tests Timer::pause(), Timer::resume() and Timer::is_paused() methods
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_pause_resume() {
+        let mut timer = Timer::new();
+        assert_eq!(timer.is_paused("test_pause"), None);
+        timer.time("test_pause");
+        assert_eq!(timer.is_paused("test_pause"), Some(false));
+        assert!(timer.pause("test_pause"));
+        assert!(!timer.pause("test_pause"));
+        assert_eq!(timer.is_paused("test_pause"), Some(true));
+        let paused_ms = timer.time_log("test_pause", true);
+        sleep(Duration::from_millis(10));
+        assert_eq!(timer.time_log("test_pause", true), paused_ms);
+        assert!(timer.resume("test_pause"));
+        assert_eq!(timer.is_paused("test_pause"), Some(false));
+        assert!(timer.time_end("test_pause", true) < 10.0);
+    }
+
+    /// Tests Timer::time_end() on a paused timer
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_end_while_paused() {
+        let mut timer = Timer::new();
+        timer.time("paused_end");
+        sleep(Duration::from_millis(10));
+        assert!(timer.pause("paused_end"));
+        sleep(Duration::from_millis(10));
+        let ms = timer.time_end("paused_end", true);
+        assert!((10.0..18.0).contains(&ms), "expected ~10ms, got {}ms", ms);
+        assert_eq!(timer.is_paused("paused_end"), None);
+    }
+
+    /// Tests Timer::time_end_logfmt() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_end_logfmt() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        timer.time("parse");
+        timer.tag("parse", "trace_id", "abc");
+        timer.tag("parse", "user", "jane doe");
+        let tags = timer.tags.get("parse").cloned().unwrap();
+        assert_eq!(
+            timer.logfmt_line("parse", 12.3, Some("cache miss"), &tags),
+            "label=parse duration_ms=12.300 msg=\"cache miss\" trace_id=abc user=\"jane doe\""
+        );
+
+        timer.time_end_logfmt("parse", None, false);
+        timer.flush();
+        let out = buf.contents();
+        assert!(out.starts_with("label=parse duration_ms="), "{}", out);
+        assert!(
+            out.trim_end().ends_with(" trace_id=abc user=\"jane doe\""),
+            "{}",
+            out
+        );
+        assert!(timer.tags.is_empty());
+    }
+
+    /// Tests Timer::time_end_logfmt() method with reentrant timers
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_end_logfmt_reentrant() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        timer.set_reentrant(true);
+        timer.time("walk");
+        timer.time("walk");
+        assert_eq!(timer.time_end_logfmt("walk", None, false), 0.0);
+        timer.flush();
+        assert_eq!(buf.contents(), "");
+
+        timer.time_end_logfmt("walk", None, false);
+        timer.flush();
+        let out = buf.contents();
+        assert_eq!(out.lines().count(), 1, "{}", out);
+        assert!(out.starts_with("label=walk duration_ms="), "{}", out);
+    }
+
+    /// Tests Timer::absorb() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_absorb() {
+        let mut worker = Timer::new();
+        worker.time("handoff");
+        worker.time("shared");
+        sleep(Duration::from_millis(10));
+
+        let mut timer = Timer::new();
+        timer.time("shared");
+        assert_eq!(timer.peak_concurrent(), 1);
+        assert_eq!(timer.absorb(worker), 1);
+        assert_eq!(timer.peak_concurrent(), 2);
+        assert!(timer.time_end("handoff", true) >= 10.0);
+        assert!(timer.time_end("shared", true) < 10.0);
+    }
+
+    /// Tests Timer::clock_resolution() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_clock_resolution() {
+        let resolution = Timer::clock_resolution();
+        assert!(resolution > Duration::ZERO);
+        assert!(resolution < Duration::from_millis(20), "{:?}", resolution);
+    }
+
+    /// Tests Timer::set_resolution_floor() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_resolution_floor() {
+        let mut timer = Timer::new();
+        timer.set_resolution_floor(Duration::from_millis(1));
+        let start = Instant::now();
+        timer.time_at("tiny", start);
+        let ms = timer.time_end_at("tiny", start + Duration::from_micros(40), true);
+        assert_eq!(ms, 1.0);
+
+        timer.time_at("slow", start);
+        let ms = timer.time_end_at("slow", start + Duration::from_millis(3), true);
+        assert_eq!(ms, 3.0);
+    }
+
+    /// Tests Timer::route_prefix() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_route_prefix() {
+        let db = SharedBuf::default();
+        let http = SharedBuf::default();
+        let default = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(default.clone()));
+        timer.route_prefix("db", Box::new(db.clone()));
+        timer.route_prefix("http", Box::new(http.clone()));
+        for label in ["db.query", "http.get", "dbx", "db"] {
+            timer.time(label);
+            timer.time_end(label, false);
+        }
+        let db = db.contents();
+        assert!(
+            db.starts_with("db.query: ") && db.contains("\ndb: "),
+            "{}",
+            db
+        );
+        assert_eq!(db.lines().count(), 2);
+        let http = http.contents();
+        assert!(
+            http.starts_with("http.get: ") && http.lines().count() == 1,
+            "{}",
+            http
+        );
+        assert!(default.contents().starts_with("dbx: "));
+    }
+
+    /// Tests Timer::time_end_with_skew() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_time_end_with_skew() {
+        let mut timer = Timer::new();
+        timer.time("untracked");
+        assert!(timer.wall_starts.is_empty());
+        let (monotonic_ms, wall_ms) = timer.time_end_with_skew("untracked");
+        assert_eq!(monotonic_ms, wall_ms);
+
+        timer.set_track_skew(true);
+        timer.time("job");
+        sleep(Duration::from_millis(10));
+        let (monotonic_ms, wall_ms) = timer.time_end_with_skew("job");
+        assert!(monotonic_ms >= 10.0);
+        assert!((wall_ms - monotonic_ms).abs() < 5.0);
+        assert!(timer.wall_starts.is_empty());
+        assert_eq!(timer.time_end_with_skew("job"), (0.0, 0.0));
+    }
+
+    /// Tests Timer::set_reentrant() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_reentrant() {
+        fn recurse(timer: &mut Timer, depth: u32, ends: &mut Vec<f64>) {
+            timer.time("recurse");
+            sleep(Duration::from_millis(5));
+            if depth > 1 {
+                recurse(timer, depth - 1, ends);
+            }
+            ends.push(timer.time_end("recurse", true));
+        }
+
+        let mut timer = Timer::new();
+        timer.set_reentrant(true);
+        let mut ends = Vec::new();
+        recurse(&mut timer, 3, &mut ends);
+        assert_eq!(ends[..2], [0.0, 0.0]);
+        assert!(ends[2] >= 15.0);
+        assert_eq!(timer.stats("recurse").unwrap().count(), 1);
+        assert!(timer.depths.is_empty());
+    }
+
+    /// Tests Timer::paused_ratio() and Timer::set_show_idle() methods
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_paused_ratio() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        timer.set_show_idle(true);
+        timer.time("io");
+        assert_eq!(timer.paused_ratio("io"), Some(0.0));
+        sleep(Duration::from_millis(20));
+        timer.pause("io");
+        sleep(Duration::from_millis(20));
+        timer.resume("io");
+        let ratio = timer.paused_ratio("io").unwrap();
+        assert!((0.35..0.65).contains(&ratio), "ratio = {}", ratio);
+
+        timer.time_end("io", false);
+        let out = buf.contents();
+        assert!(out.contains("% idle)"), "{}", out);
+        assert_eq!(timer.paused_ratio("io"), None);
+    }
+
+    /// Tests that Timer::measure() attributes parents per timer when timers interleave
+    #[test]
+    fn test_timer_measure_interleaved() {
+        let mut app = Timer::new();
+        let mut db = Timer::new();
+        {
+            let mut pool = db.scope("pool");
+            app.measure("request", |app| {
+                pool.measure("query", |db| db.measure("fetch", |_| ()));
+                app.measure("render", |_| ());
+            });
+        }
+        assert_eq!(app.parent("request"), None);
+        assert_eq!(app.parent("render"), Some("request"));
+        assert_eq!(db.parent("query"), Some("pool"));
+        assert_eq!(db.parent("fetch"), Some("query"));
+        assert!(app.tree_report().starts_with("request: "));
+        assert!(db.tree_report().starts_with("pool: "));
+    }
+
+    /// Tests Timer::measure() and Timer::tree_report() methods
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_measure_tree() {
+        let mut timer = Timer::new();
+        let value = timer.measure("request", |timer| {
+            let parsed = timer.measure("parse", |_| 2);
+            timer.measure("render", |timer| timer.measure("layout", |_| parsed * 10))
+        });
+        assert_eq!(value, 20);
+        timer.measure("idle", |_| ());
+        assert_eq!(timer.parent("parse"), Some("request"));
+        assert_eq!(timer.parent("layout"), Some("render"));
+        assert_eq!(timer.parent("idle"), None);
+
+        let report = timer.tree_report();
+        assert_eq!(
+            report
+                .lines()
+                .map(|line| line.split(':').next().unwrap())
+                .collect::<Vec<_>>(),
+            [
+                "idle",
+                "request",
+                "  parse",
+                "  render",
+                "    layout",
+                "    <self>",
+                "  <self>"
+            ]
+        );
+    }
+
+    /// Tests the `<self>` nodes of Timer::tree_report()
+    #[test]
+    fn test_timer_tree_report_self_time() {
+        let mut timer = Timer::new();
+        timer.record("request", from_ms(100.0));
+        timer.record("parse", from_ms(40.0));
+        timer.record("render", from_ms(30.0));
+        timer.record("io", from_ms(20.0));
+        timer.record("retry", from_ms(25.0));
+        for (child, parent) in [("parse", "request"), ("render", "request"), ("retry", "io")] {
+            timer.parents.insert(child.to_string(), parent.to_string());
+        }
+        assert_eq!(
+            timer.tree_report(),
+            "io: 20.000ms (1 calls)\n  retry: 25.000ms (1 calls)\n  <self>: 0.000ms\n\
+             request: 100.000ms (1 calls)\n  parse: 40.000ms (1 calls)\n  \
+             render: 30.000ms (1 calls)\n  <self>: 30.000ms\n"
+        );
+    }
+
+    /// Tests Timer::set_label_sink() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_set_label_sink() {
+        let sink = SharedBuf::default();
+        let db = SharedBuf::default();
+        let default = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(default.clone()));
+        timer.route_prefix("db", Box::new(db.clone()));
+        timer.set_label_sink("db.query", Box::new(sink.clone()));
+        for label in ["db.query", "db.query.rows", "db.connect", "render"] {
+            timer.time(label);
+            timer.time_end(label, false);
+        }
+        let sink = sink.contents();
+        assert!(
+            sink.starts_with("db.query: ") && sink.lines().count() == 1,
+            "{}",
+            sink
+        );
+        let db = db.contents();
+        assert!(
+            db.starts_with("db.query.rows: ") && db.contains("\ndb.connect: "),
+            "{}",
+            db
+        );
+        assert!(default.contents().starts_with("render: "));
+        assert_eq!(default.contents().lines().count(), 1);
+    }
+
+    /// Tests Timer::timed_iter() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_timed_iter() {
+        let mut timer = Timer::new();
+        let slow = (1..=4).map(|n| {
+            sleep(Duration::from_millis(2));
+            n * 2
+        });
+        let items: Vec<u32> = timer.timed_iter("parse", slow).collect();
+        assert_eq!(items, vec![2, 4, 6, 8]);
+        let stats = timer.stats("parse").unwrap();
+        assert_eq!(stats.count(), 4);
+        assert!(stats.total_ms() >= 6.0);
+        assert!((stats.mean_ms() - stats.total_ms() / 4.0).abs() < 1e-9);
+
+        assert_eq!(timer.timed_iter("empty", Vec::<u32>::new()).count(), 0);
+        assert!(timer.stats("empty").is_none());
+    }
+
+    /// Tests Timer::set_epoch() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_set_epoch() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        let epoch = Instant::now();
+        timer.set_epoch(epoch);
+        sleep(Duration::from_millis(10));
+        timer.time("handler");
+        let end = epoch + Duration::from_millis(100);
+        let ms = timer.time_end_at("handler", end, false);
+        assert!(ms < 90.0, "ms = {}", ms);
+        let expected = format!("handler: +{}ms @ offset 100.000ms", timer.format_ms(ms));
+        assert!(buf.contents().starts_with(&expected), "{}", buf.contents());
+    }
+
+    /// Tests Timer::weighted_total() method
+    #[test]
+    fn test_timer_weighted_total() {
+        let mut timer = Timer::new();
+        timer.record("render", from_ms(10.0));
+        timer.record("render", from_ms(30.0));
+        timer.record("fetch", from_ms(50.0));
+        let score = timer.weighted_total(&[("render", 2.0), ("fetch", 0.5), ("missing", 3.0)]);
+        assert!((score - 65.0).abs() < 1e-9);
+        assert_eq!(timer.weighted_total(&[]), 0.0);
+    }
+
+    /// Tests Timer::clear_prefix() method
+    #[test]
+    fn test_timer_clear_prefix() {
+        let mut timer = Timer::new();
+        timer.set_track_skew(true);
+        timer.scope("a.x").scope("a.child").scope("b.z");
+        for label in ["a.x", "a.y", "b.z", "ab"] {
+            timer.time_every_n(label, 1);
+            timer.time_end(label, true);
+            timer.time_here(label);
+            timer.tag(label, "k", "v");
+        }
+        timer.time_cat("a.cat", "io");
+        timer.time_end("a.cat", true);
+        timer.time_cat("a.cat", "io");
+        assert_eq!(timer.clear_prefix("a"), 7);
+        for label in ["a.x", "a.y", "a.cat", "a.child"] {
+            assert!(!timer.timers.contains_key(label));
+            assert!(timer.stats(label).is_none());
+            assert!(!timer.locations.contains_key(label));
+            assert!(!timer.categories.contains_key(label));
+            assert!(!timer.sample_counters.contains_key(label));
+            assert!(!timer.tags.contains_key(label));
+            assert!(!timer.wall_starts.contains_key(label));
+            assert!(timer.parent(label).is_none());
+            #[cfg(feature = "alloc-count")]
+            assert!(!timer.alloc_starts.contains_key(label));
+        }
+        assert!(timer.parents.is_empty());
+        for label in ["b.z", "ab"] {
+            assert!(timer.timers.contains_key(label));
+            assert!(timer.stats(label).is_some());
+            assert!(timer.locations.contains_key(label));
+            assert!(timer.tags.contains_key(label));
+            assert!(timer.sample_counters.contains_key(label));
+        }
+    }
+
+    /// Tests Timer::set_max_label_len() method
+    #[test]
+    fn test_timer_max_label_len() {
+        let mut timer = Timer::new();
+        timer.set_max_label_len(Some(8), LabelLengthPolicy::Truncate);
+        timer.time("request_1234567");
+        assert!(timer.timers.contains_key("request_"));
+        timer.time_end("request_1234567", true);
+        assert_eq!(timer.stats("request_").unwrap().count(), 1);
+
+        timer.set_max_label_len(Some(8), LabelLengthPolicy::Reject);
+        timer.time("request_1234567");
+        assert!(timer.timers.is_empty());
+        timer.time("short");
+        assert!(timer.timers.contains_key("short"));
+    }
+
+    /// Tests Timer::set_channel() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_set_channel() {
+        let (tx, rx) = std::sync::mpsc::channel();
         let mut timer = Timer::new();
-        timer.time("test_time_end");
+        timer.set_channel(tx);
+        timer.time("test_channel");
+        let ms = timer.time_end("test_channel", true);
+        let (label, duration) = rx.try_recv().unwrap();
+        assert_eq!(label, "test_channel");
+        assert_eq!(Timer::duration_to_ms(duration), ms);
+        assert!(rx.try_recv().is_err());
+
+        drop(rx);
+        timer.time("test_channel");
+        timer.time_end("test_channel", true);
+        assert!(timer.channel.is_none());
+    }
+
+    /// Tests Timer::drain_stats() method
+    #[test]
+    fn test_timer_drain_stats() {
+        let mut timer = Timer::new();
+        timer.set_retain_samples(true);
+        timer.record("flush", from_ms(4.0));
+        timer.record("flush", from_ms(6.0));
+        timer.record("poll", from_ms(1.0));
+
+        let drained = timer.drain_stats();
+        assert_eq!(drained.len(), 2);
+        assert_eq!(drained["flush"].count(), 2);
+        assert!((drained["flush"].total_ms() - 10.0).abs() < 1e-9);
+        assert_eq!(drained["poll"].max_ms(), 1.0);
+        assert!(timer.stats("flush").is_none());
+        assert!(timer.all_stats().is_empty());
+        assert_eq!(timer.samples("flush"), None);
+        assert!(timer.drain_stats().is_empty());
+
+        timer.record("flush", from_ms(2.0));
+        assert_eq!(timer.drain_stats()["flush"].count(), 1);
+    }
+
+    /// Tests Timer::report_table() method
+    #[test]
+    fn test_timer_report_table() {
+        let mut timer = Timer::new();
+        assert_eq!(timer.report_table(), "");
+        timer.record("db", from_ms(1.5));
+        timer.record("db", from_ms(2.5));
+        timer.record("render_template", from_ms(1234.0));
+        assert_eq!(
+            timer.report_table(),
+            "Label            Count       Total        Mean         Min         Max\n\
+             ---------------  -----  ----------  ----------  ----------  ----------\n\
+             db                   2     4.000ms     2.000ms     1.500ms     2.500ms\n\
+             render_template      1  1234.000ms  1234.000ms  1234.000ms  1234.000ms\n"
+        );
+    }
+
+    /// Tests Timer::stddev_ms() method
+    #[test]
+    fn test_timer_stddev_ms() {
+        let mut timer = Timer::new();
+        let stats = timer.stats.entry("test_stddev".to_string()).or_default();
+        stats.record(from_ms(2.0));
+        assert_eq!(timer.stddev_ms("test_stddev"), None);
+        let stats = timer.stats.get_mut("test_stddev").unwrap();
+        for ms in [4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.record(from_ms(ms));
+        }
+        // Sample variance of the dataset is 32 / 7.
+        let expected = (32.0f64 / 7.0).sqrt();
+        assert!((timer.stddev_ms("test_stddev").unwrap() - expected).abs() < 1e-9);
+        let stats = timer.stats("test_stddev").unwrap();
+        assert!((stats.variance_ms() - 32.0 / 7.0).abs() < 1e-9);
+        assert!((stats.std_dev_ms() - expected).abs() < 1e-9);
+        assert!(timer.report().contains(", std dev 2.138ms, "));
+        assert_eq!(timer.stddev_ms("missing"), None);
+    }
+
+    /// Tests Timer::cv() method
+    #[test]
+    fn test_timer_cv() {
+        let mut timer = Timer::new();
+        timer.record("steady", from_ms(10.0));
+        assert_eq!(timer.cv("steady"), None);
+        timer.record("steady", from_ms(10.0));
+        assert_eq!(timer.cv("steady"), Some(0.0));
+
+        for ms in [4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0, 2.0] {
+            timer.record("noisy", from_ms(ms));
+        }
+        let expected = (32.0f64 / 7.0).sqrt() / 5.0;
+        assert!((timer.cv("noisy").unwrap() - expected).abs() < 1e-9);
+
+        timer.record("instant", from_ms(0.0));
+        timer.record("instant", from_ms(0.0));
+        assert_eq!(timer.cv("instant"), None);
+        assert_eq!(timer.cv("missing"), None);
+    }
+
+    /// Tests concurrent timing through AsyncTimer
+    #[test]
+    #[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+    fn test_async_timer() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(4)
+            .enable_time()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let timer = AsyncTimer::new();
+            let tasks: Vec<_> = (0..8)
+                .map(|i| {
+                    let timer = timer.clone();
+                    tokio::spawn(async move {
+                        let label = format!("task_{}", i);
+                        timer.time(&label).await;
+                        tokio::time::sleep(Duration::from_millis(5)).await;
+                        timer.time_end(&label, true).await
+                    })
+                })
+                .collect();
+            for task in tasks {
+                assert!(task.await.unwrap() >= 5.0);
+            }
+            assert_eq!(timer.stats("task_3").await.unwrap().count(), 1);
+            assert!(timer.with_timer(|timer| timer.timers.is_empty()).await);
+        });
+    }
+
+    /// Tests Timer::histogram() method
+    #[test]
+    fn test_timer_histogram() {
+        let mut timer = Timer::new();
+        timer.set_retain_samples(true);
+        for ms in [1.0, 4.0, 5.0, 12.0, 60.0] {
+            timer.record("test_histogram", from_ms(ms));
+        }
+        assert_eq!(
+            timer.histogram("test_histogram", &[10.0, 5.0, 50.0]),
+            vec![(5.0, 3), (10.0, 3), (50.0, 4), (f64::INFINITY, 5)]
+        );
+        assert_eq!(
+            timer.histogram("missing", &[5.0]),
+            vec![(5.0, 0), (f64::INFINITY, 0)]
+        );
+    }
+
+    /// Tests Timer::set_label_normalizer() method
+    #[test]
+    fn test_timer_label_normalizer() {
+        let mut timer = Timer::new();
+        timer.set_label_normalizer(Box::new(Timer::trim_lowercase));
+        timer.time(" parse ");
+        assert!(timer.timers.contains_key("parse"));
+        timer.time_end("parse", true);
+        timer.time("parse");
+        timer.time_end("  parse", true);
+        assert_eq!(timer.stats(" parse").unwrap().count(), 2);
+        assert_eq!(timer.stats.len(), 1);
+    }
+
+    /// Tests Timer::set_display_mapper() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_display_mapper() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        timer.set_display_mapper(Box::new(|label: &str| match label {
+            "db.q1" => "Database Query 1".to_string(),
+            other => other.to_string(),
+        }));
+        timer.time("db.q1");
+        timer.time_end("db.q1", false);
+        assert!(buf.contents().starts_with("Database Query 1: "));
+        assert_eq!(timer.stats("db.q1").unwrap().count(), 1);
+        assert!(timer.stats("Database Query 1").is_none());
+        assert!(timer.report().contains("  Database Query 1: 1 calls"));
+
+        timer.clear_display_mapper();
+        assert_eq!(timer.end_message("db.q1", 1.0), "db.q1: 1.000ms");
+    }
+
+    /// Tests Timer::unify_separators() normalizer
+    #[test]
+    fn test_timer_unify_separators() {
+        assert_eq!(Timer::unify_separators("db-query"), "db_query");
+        assert_eq!(Timer::unify_separators(" DB  Query "), "db_query");
+        assert_eq!(Timer::unify_separators("db__query-"), "db_query");
+
+        let mut timer = Timer::new();
+        timer.set_label_normalizer(Box::new(|label: &str| label.to_lowercase()));
+        timer.time("DB");
+        assert!(timer.time_end("db", true) >= 0.0);
+        assert_eq!(timer.stats("Db").unwrap().count(), 1);
+
+        timer.set_label_normalizer(Box::new(Timer::unify_separators));
+        timer.time("DB Query");
+        timer.time_end("db-query", true);
+        assert_eq!(timer.stats("db_query").unwrap().count(), 1);
+    }
+
+    /// Tests Timer::set_scale() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_set_scale() {
+        let mut timer = Timer::new();
+        timer.set_scale(0.5);
+        timer.set_scale(-1.0);
+        let start = Instant::now();
+        timer.timers.insert("scaled".to_string(), start);
+        let ms = timer.time_end_at("scaled", start + Duration::from_millis(25), true);
+        assert_eq!(ms, 12.5);
+        assert_eq!(timer.stats("scaled").unwrap().total_ms(), 12.5);
+    }
+
+    #[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator::new(std::alloc::System);
+
+    /// Tests allocation counting with the alloc-count feature
+    #[test]
+    #[cfg(all(feature = "alloc-count", not(target_arch = "wasm32")))]
+    fn test_timer_allocations() {
+        let mut timer = Timer::new();
+        timer.time("test_allocs");
+        let boxes: Vec<Box<u64>> = (0..10).map(Box::new).collect();
+        timer.time_end("test_allocs", true);
+        assert_eq!(boxes.len(), 10);
+        assert!(timer.allocations("test_allocs").unwrap() >= 11);
+    }
+
+    /// Tests Timer::set_buffer_threshold() and Timer::flush() methods
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_buffered_output() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        timer.set_buffer_threshold(Some(5));
+        for i in 0..4 {
+            let label = format!("buffered_{}", i);
+            timer.time(&label);
+            timer.time_end(&label, false);
+        }
+        assert_eq!(timer.pending_lines(), 4);
+        assert!(buf.contents().is_empty());
+        timer.time("buffered_4");
+        timer.time_end("buffered_4", false);
+        assert_eq!(timer.pending_lines(), 0);
+        assert_eq!(buf.contents().lines().count(), 5);
+
+        timer.time("buffered_5");
+        timer.time_end("buffered_5", false);
+        assert_eq!(buf.contents().lines().count(), 5);
+        timer.flush();
+        let contents = buf.contents();
+        assert_eq!(contents.lines().count(), 6);
+        assert!(contents.lines().last().unwrap().starts_with("buffered_5: "));
+
+        timer.time("buffered_6");
+        timer.time_end("buffered_6", false);
+        drop(timer);
+        assert_eq!(buf.contents().lines().count(), 7);
+    }
+
+    /// Tests the Timing trait on LocalTimer and SharedTimer
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timing_trait() {
+        fn measure<T: Timing>(timer: &mut T, label: &str) -> f64 {
+            timer.time(label);
+            timer.time_log(label, true);
+            timer.time_end(label, true)
+        }
+
+        let mut local = LocalTimer::new();
+        measure(&mut local, "local");
+        assert_eq!(local.stats("local").unwrap().count(), 1);
+
+        let shared = SharedTimer::new();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let mut shared = shared.clone();
+                std::thread::spawn(move || measure(&mut shared, "shared"))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(shared.stats("shared").unwrap().count(), 4);
+    }
+
+    /// Tests the NoopTimer Timing implementation
+    #[test]
+    fn test_noop_timer() {
+        fn instrumented<T: Timing>(timer: &mut T) -> f64 {
+            timer.time("noop");
+            timer.time_end("noop", false)
+        }
+
+        let mut timer = NoopTimer::new();
+        assert_eq!(instrumented(&mut timer), 0.0);
+        assert_eq!(timer.time_log("noop", false), 0.0);
+    }
+
+    /// Tests Timer::ratio() and Timer::faster_of() methods
+    #[test]
+    fn test_timer_ratio() {
+        let mut timer = Timer::new();
+        for ms in [10.0, 30.0] {
+            timer.record("slow", from_ms(ms));
+        }
+        for ms in [4.0, 6.0] {
+            timer.record("fast", from_ms(ms));
+        }
+        assert_eq!(timer.ratio("slow", "fast"), Some(4.0));
+        assert_eq!(timer.ratio("fast", "slow"), Some(0.25));
+        assert_eq!(timer.faster_of("slow", "fast"), Some("fast"));
+        assert_eq!(timer.ratio("slow", "missing"), None);
+        assert_eq!(timer.faster_of("missing", "fast"), None);
+    }
+
+    /// Tests Timer::time_cat() and Timer::set_category_filter() methods
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_category_filter() {
+        let buf = SharedBuf::default();
+        let mut timer = Timer::new();
+        timer.set_writer(Box::new(buf.clone()));
+        timer.set_category_filter(&["coarse"]);
+        timer.time_cat("request", "coarse");
+        timer.time_cat("lock_acquire", "fine");
+        timer.time("plain");
+        timer.time_end("lock_acquire", false);
+        timer.time_end("request", false);
+        timer.time_end("plain", false);
+        let contents = buf.contents();
+        let labels: Vec<&str> = contents
+            .lines()
+            .map(|line| line.split(':').next().unwrap())
+            .collect();
+        assert_eq!(labels, vec!["request", "plain"]);
+        assert_eq!(timer.stats("lock_acquire").unwrap().count(), 1);
+    }
+
+    /// Tests Timer::since_process_start() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_since_process_start() {
+        let timer = Timer::new();
+        let first = timer.since_process_start();
+        sleep(Duration::from_millis(5));
+        let second = timer.since_process_start();
+        assert!(second >= first + 5.0);
+    }
+
+    /// Tests the percent of total in Timer::report() output
+    #[test]
+    fn test_timer_report_percent_of_total() {
+        let mut timer = Timer::new();
+        timer.record("a", from_ms(30.0));
+        timer.record("b", from_ms(10.0));
+        timer.record("b", from_ms(60.0));
+        let report = timer.report();
+        assert!(report.contains("  a: 1 calls, total 30.000ms, mean 30.000ms, min 30.000ms, max 30.000ms, std dev 0.000ms, 30.0% of total\n"));
+        assert!(report.contains(", 70.0% of total\n"));
+
+        let mut idle = Timer::new();
+        idle.record("zero", from_ms(0.0));
+        assert!(idle.report().contains(", 0.0% of total\n"));
+    }
+
+    /// Tests Timer::report_json() method
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_timer_report_json() {
+        let mut timer = Timer::new();
+        timer.record("a", from_ms(1.0));
+        timer.record("a", from_ms(2.0));
+        timer.count_by("hits", 3);
+        assert_eq!(
+            timer.report_json(),
+            "{\"timers\":[{\"label\":\"a\",\"count\":2,\"total_ms\":3,\"mean_ms\":1.5,\"min_ms\":1,\"max_ms\":2,\"std_dev_ms\":0.7071067811865476,\"variance_ms\":0.5,\"percent_of_total\":100}],\"counters\":{\"hits\":3}}"
+        );
+    }
+
+    /// Tests Timer::report_chrome_trace() method
+    #[test]
+    #[cfg(all(feature = "json", not(target_arch = "wasm32")))]
+    fn test_timer_report_chrome_trace() {
+        let mut timer = Timer::new();
+        assert_eq!(timer.report_chrome_trace(), "[]");
+        timer.set_record_trace(true);
+        timer.time("outer");
+        timer.time("inner");
+        let start = timer.timers["outer"];
+        timer.time_end_at(
+            "inner",
+            timer.timers["inner"] + Duration::from_millis(2),
+            true,
+        );
+        timer.time_end_at("outer", start + Duration::from_millis(5), true);
+
+        let trace = timer.report_chrome_trace();
+        assert!(trace.starts_with("[{\"name\":\"inner\",\"ph\":\"X\",\"ts\":"));
+        assert!(trace.contains("\"dur\":2000,"));
+        assert!(trace.contains("{\"name\":\"outer\",\"ph\":\"X\",\"ts\":"));
+        assert!(trace.contains("\"dur\":5000,"));
+        assert!(trace.contains(&format!("\"pid\":{},", std::process::id())));
+    }
+
+    /// Tests Timer::save_state() and Timer::restore_state() methods
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_save_restore_state() {
+        let mut timer = Timer::new();
+        timer.time("running");
+        timer.record("done", from_ms(5.0));
+        timer.count("hits");
+        let start = timer.timers["running"];
+        let state = timer.save_state();
+        assert!(state.is_running("running"));
+
+        timer.time_end("running", true);
+        timer.record("done", from_ms(7.0));
+        timer.count("hits");
+        timer.time("speculative");
+
+        timer.restore_state(state.clone());
+        assert_eq!(timer.timers.len(), 1);
+        assert_eq!(timer.timers["running"], start);
+        assert_eq!(timer.stats("done").unwrap().count(), 1);
+        assert_eq!(timer.stats("running"), None);
+        assert_eq!(timer.counter("hits"), Some(1));
+        assert_eq!(state.stats("done").unwrap().total_ms(), 5.0);
+    }
+
+    /// Tests Timer::restore_state() method after a pause and resume
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_restore_state_after_pause() {
+        let mut timer = Timer::new();
+        timer.set_reentrant(true);
+        timer.time("io");
+        timer.pause("io");
+        sleep(Duration::from_millis(40));
+        timer.resume("io");
+        let state = timer.save_state();
+
+        timer.time("io");
+        timer.tag("io", "phase", "retry");
+        timer.restore_state(state);
+        assert!(timer.depths.is_empty());
+        assert!(timer.tags.is_empty());
+        let ms = timer.time_end("io", true);
+        assert!(ms < 30.0, "ms = {}", ms);
+        assert_eq!(timer.stats("io").unwrap().count(), 1);
+    }
+
+    /// Tests Timer::compare_to_baseline() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_compare_to_baseline() {
+        let mut baseline = TimerState::default();
+        for (label, ms) in [("parse", 10.0), ("render", 20.0), ("removed", 5.0)] {
+            baseline
+                .stats
+                .entry(label.to_string())
+                .or_default()
+                .record(from_ms(ms));
+        }
+
+        let mut timer = Timer::new();
+        timer.record("parse", from_ms(13.0));
+        timer.record("render", from_ms(21.0));
+        timer.record("added", from_ms(50.0));
+
+        let regressions = timer.compare_to_baseline(&baseline, 10.0);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].label, "parse");
+        assert_eq!(regressions[0].baseline_mean_ms, 10.0);
+        assert_eq!(regressions[0].current_mean_ms, 13.0);
+        assert!((regressions[0].change_pct - 30.0).abs() < 1e-9);
+        assert_eq!(timer.compare_to_baseline(&baseline, 2.0).len(), 2);
+    }
+
+    /// Tests Timer::rename() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_rename() {
+        let mut timer = Timer::new();
+        timer.time("request");
         sleep(Duration::from_millis(10));
-        timer.time_end("test_time_end", false);
-        assert!(!timer.timers.contains_key("test"));
+        timer.time("taken");
+        assert!(!timer.rename("request", "taken"));
+        assert!(!timer.rename("missing", "other"));
+        assert!(timer.rename("request", "GET /users"));
+        assert!(!timer.timers.contains_key("request"));
+        assert!(timer.time_end("GET /users", true) >= 10.0);
+    }
+
+    /// Tests Timer::set_missing_timer_warning() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_missing_timer_warning() {
+        let mut timer = Timer::new();
+        assert!(timer.warn_missing("missing"));
+        assert!(timer.warn_missing("missing"));
+
+        timer.set_missing_timer_warning(MissingTimerWarning::Once);
+        assert!(timer.warn_missing("missing"));
+        assert!(!timer.warn_missing("missing"));
+        assert!(timer.warn_missing("other"));
+        assert_eq!(timer.time_log("missing", true), 0.0);
+
+        timer
+            .set_missing_timer_warning(MissingTimerWarning::RateLimited(Duration::from_millis(20)));
+        assert!(timer.warn_missing("missing"));
+        assert!(!timer.warn_missing("missing"));
+        sleep(Duration::from_millis(25));
+        assert!(timer.warn_missing("missing"));
+    }
+
+    /// Tests Timer::format_clock() and Timer::time_end_clock() methods
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_format_clock() {
+        assert_eq!(
+            Timer::format_clock(Duration::from_millis(83_456)),
+            "00:01:23.456"
+        );
+        assert_eq!(Timer::format_clock(Duration::ZERO), "00:00:00.000");
+        assert_eq!(
+            Timer::format_clock(Duration::from_secs(3 * 3600 + 59 * 60 + 7)),
+            "03:59:07.000"
+        );
+
+        let mut timer = Timer::new();
+        timer.time("job");
+        assert!(timer.time_end_clock("job").starts_with("00:00:00."));
+        assert_eq!(timer.time_end_clock("job"), "00:00:00.000");
+    }
+
+    /// Tests Timer::top_slowest() and Timer::top_slowest_by_total() methods
+    #[test]
+    fn test_timer_top_slowest() {
+        let mut timer = Timer::new();
+        timer.record("parse", from_ms(5.0));
+        timer.record("render", from_ms(20.0));
+        timer.record("query", from_ms(12.0));
+        for _ in 0..3 {
+            timer.record("parse", from_ms(5.0));
+        }
+
+        assert_eq!(
+            timer.top_slowest(2),
+            vec![("render".to_string(), 20.0), ("query".to_string(), 12.0)]
+        );
+        assert_eq!(
+            timer.top_slowest_by_total(2),
+            vec![("parse".to_string(), 20.0), ("render".to_string(), 20.0)]
+        );
+        assert_eq!(timer.top_slowest(10).len(), 3);
+        assert!(timer.top_slowest(0).is_empty());
+    }
+
+    /// Tests Timer::set_weight() and Timer::top_by_weighted_total() methods
+    #[test]
+    fn test_timer_top_by_weighted_total() {
+        let mut timer = Timer::new();
+        timer.record("export", from_ms(30.0));
+        timer.record("search", from_ms(10.0));
+        timer.record("login", from_ms(5.0));
+        assert_eq!(
+            timer.top_by_weighted_total(3),
+            timer.top_slowest_by_total(3)
+        );
+
+        timer.set_weight("search", 5.0);
+        timer.set_weight("export", 0.1);
+        assert_eq!(
+            timer.top_by_weighted_total(3),
+            vec![
+                ("search".to_string(), 50.0),
+                ("login".to_string(), 5.0),
+                ("export".to_string(), 3.0)
+            ]
+        );
+    }
+
+    /// Tests SharedTimer::self_overhead_ms() method
+    #[test]
+    #[cfg(all(feature = "self-metrics", not(target_arch = "wasm32")))]
+    fn test_shared_timer_self_overhead() {
+        let shared = SharedTimer::new();
+        assert_eq!(shared.self_overhead_ms(), 0.0);
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let shared = shared.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        shared.with_timer(|_| sleep(Duration::from_millis(1)));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(shared.self_overhead_ms() > 0.0);
+    }
+
+    /// Tests Timer::merge() and TimerStats::merge() methods
+    #[test]
+    fn test_timer_merge() {
+        let mut a = Timer::new();
+        let mut b = Timer::new();
+        for ms in [2.0, 4.0, 4.0, 4.0] {
+            a.record("parse", from_ms(ms));
+        }
+        for ms in [5.0, 5.0, 7.0, 9.0] {
+            b.record("parse", from_ms(ms));
+        }
+        b.record("render", from_ms(10.0));
+        b.count_by("hits", 2);
+        a.merge(&b);
+
+        let merged = a.stats("parse").unwrap();
+        assert_eq!(merged.count(), 8);
+        assert_eq!(merged.mean_ms(), 40.0 / 8.0);
+        assert!((merged.variance_ms() - 32.0 / 7.0).abs() < 1e-9);
+        assert_eq!(merged.min_ms(), 2.0);
+        assert_eq!(merged.max_ms(), 9.0);
+        assert_eq!(a.stats("render").unwrap().count(), 1);
+        assert_eq!(a.counter("hits"), Some(2));
+
+        // Unequal counts: the combined mean is total over count, not the mean of the means.
+        let mut small = TimerStats::default();
+        small.record(from_ms(10.0));
+        let mut large = TimerStats::default();
+        for _ in 0..9 {
+            large.record(from_ms(1.0));
+        }
+        small.merge(&large);
+        assert!((small.mean_ms() - 19.0 / 10.0).abs() < 1e-9);
+    }
+
+    /// Tests Timer::since_last_end() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_since_last_end() {
+        let mut timer = Timer::new();
+        assert_eq!(timer.since_last_end(), None);
+        timer.time("a");
+        timer.time_end("a", true);
+        sleep(Duration::from_millis(10));
+        let gap = timer.since_last_end().unwrap();
+        assert!(gap >= 10.0);
+        timer.time("b");
+        timer.time_end("b", true);
+        assert!(timer.since_last_end().unwrap() < gap);
+        timer.time_end("missing", true);
+        assert!(timer.since_last_end().unwrap() < gap);
+    }
+
+    /// Tests Timer::peak_concurrent() and Timer::reset_peak() methods
+    #[test]
+    fn test_timer_peak_concurrent() {
+        let mut timer = Timer::new();
+        assert_eq!(timer.peak_concurrent(), 0);
+        timer.time_many(&["a", "b", "c"]);
+        assert_eq!(timer.peak_concurrent(), 3);
+        timer.time_end("a", true);
+        timer.time_end("b", true);
+        assert_eq!(timer.peak_concurrent(), 3);
+        timer.time("c");
+        assert_eq!(timer.peak_concurrent(), 3);
+        timer.reset_peak();
+        assert_eq!(timer.peak_concurrent(), 1);
+    }
+
+    /// Tests Timer::snapshot_map() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_snapshot_map() {
+        let mut timer = Timer::new();
+        timer.time("a");
+        timer.time("b");
+        sleep(Duration::from_millis(5));
+        let snapshot = timer.snapshot_map();
+        timer.time_end("a", true);
+        assert_eq!(snapshot.len(), 2);
+        assert!(snapshot["a"] >= Duration::from_millis(5));
+        assert!(snapshot.contains_key("b"));
+
+        let shared = SharedTimer::new();
+        shared.time("c");
+        assert_eq!(shared.snapshot_map().len(), 1);
+    }
+
+    /// Tests Timer::start_measurement() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_start_measurement() {
+        let measurement = Timer::start_measurement("upload");
+        assert_eq!(measurement.label(), "upload");
+        sleep(Duration::from_millis(10));
+        let completed = measurement.stop();
+        assert_eq!(completed.label, "upload");
+        assert!(completed.elapsed >= Duration::from_millis(10));
+        assert!(completed.elapsed_ms() >= 10.0);
+    }
+
+    /// Tests that SharedTimer keeps working after its mutex is poisoned
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_shared_timer_poison_recovery() {
+        let shared = SharedTimer::new();
+        shared.time("before");
+        let poisoner = shared.clone();
+        let result = std::thread::spawn(move || {
+            poisoner.with_timer(|_| panic!("panic while holding the timer lock"));
+        })
+        .join();
+        assert!(result.is_err());
+
+        assert!(shared.time_end("before", true) >= 0.0);
+        shared.time("after");
+        assert!(shared.time_log("after", true) >= 0.0);
+        shared.time_end("after", true);
+        assert_eq!(shared.stats("after").unwrap().count(), 1);
+    }
+
+    /// Tests SharedTimer::try_time() and SharedTimer::try_time_end() methods
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_shared_timer_try_lock() {
+        let shared = SharedTimer::new();
+        assert!(shared.try_time("fast"));
+
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let holder = shared.clone();
+        let handle = std::thread::spawn(move || {
+            holder.with_timer(|_| {
+                locked_tx.send(()).unwrap();
+                release_rx.recv().unwrap();
+            });
+        });
+        locked_rx.recv().unwrap();
+        let start = Instant::now();
+        assert!(!shared.try_time("blocked"));
+        assert_eq!(shared.try_time_end("fast", true), None);
+        assert!(start.elapsed() < Duration::from_millis(50));
+        release_tx.send(()).unwrap();
+        handle.join().unwrap();
+
+        assert!(shared.try_time_end("fast", true).unwrap() >= 0.0);
+        assert_eq!(shared.stats("blocked"), None);
+    }
+
+    /// Tests SharedTimer::session() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_shared_timer_sessions() {
+        let shared = SharedTimer::new();
+        shared.time("global");
+        let first = shared.session();
+        let second = shared.session();
+        assert_ne!(first.prefix(), second.prefix());
+
+        let worker = std::thread::spawn(move || {
+            second.time("db");
+            second.time_end("db", true);
+            second.finish()
+        });
+        first.time("parse");
+        first.time_end("parse", true);
+        let first_report = first.report();
+        let second_report = worker.join().unwrap();
+
+        assert!(
+            first_report.starts_with("Timers:\n  parse: 1 calls"),
+            "{}",
+            first_report
+        );
+        assert!(!first_report.contains("db"));
+        assert!(
+            second_report.starts_with("Timers:\n  db: 1 calls"),
+            "{}",
+            second_report
+        );
+        assert!(!second_report.contains("parse"));
+
+        assert!(first.stats("parse").is_some());
+        first.finish();
+        shared.time_end("global", true);
+        let report = shared.with_timer(|timer| timer.report());
+        assert!(
+            report.starts_with("Timers:\n  global: 1 calls"),
+            "{}",
+            report
+        );
+        assert_eq!(report.lines().count(), 2);
+    }
+
+    /// Tests SharedTimer::guard_lock() method
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_shared_timer_guard_lock() {
+        let shared = SharedTimer::new();
+        shared.with_timer(|timer| timer.set_writer(Box::new(SharedBuf::default())));
+        let data = Mutex::new(vec![1]);
+        {
+            let mut guard = shared.guard_lock("data_lock", data.lock().unwrap());
+            guard.push(2);
+            sleep(Duration::from_millis(20));
+        }
+        assert!(data.try_lock().is_ok());
+        assert_eq!(*data.lock().unwrap(), [1, 2]);
+        let stats = shared.stats("data_lock").unwrap();
+        assert_eq!(stats.count(), 1);
+        assert!(stats.total_ms() >= 20.0 && stats.total_ms() < 100.0);
+    }
+
+    /// Tests Timer::report_openmetrics() method
+    #[test]
+    #[cfg(all(feature = "prometheus", not(target_arch = "wasm32")))]
+    fn test_timer_report_openmetrics() {
+        let mut timer = Timer::new();
+        timer.set_retain_samples(true);
+        assert!(!timer.tag("request", "trace_id", "abc"));
+        timer.time("request");
+        assert!(timer.tag("request", "trace_id", "abc"));
+        let start = timer.timers["request"];
+        timer.time_end_at("request", start + Duration::from_millis(30), true);
+        timer.time("request");
+        let start = timer.timers["request"];
+        timer.time_end_at("request", start + Duration::from_millis(5), true);
+
+        let text = timer.report_openmetrics(&[10.0, 50.0]);
+        assert!(text.starts_with("# TYPE timelog_duration_seconds histogram\n"));
+        assert!(text.contains("timelog_duration_seconds_bucket{label=\"request\",le=\"0.01\"} 1\n"));
+        assert!(text.contains(
+            "timelog_duration_seconds_bucket{label=\"request\",le=\"0.05\"} 2 # {trace_id=\"abc\"} 0.03 "
+        ));
+        assert!(text.contains("timelog_duration_seconds_bucket{label=\"request\",le=\"+Inf\"} 2\n"));
+        assert!(text.contains("timelog_duration_seconds_count{label=\"request\"} 2\n"));
+        assert!(text.ends_with("# EOF\n"));
+    }
+
+    /// Tests that elapsed times are clamped to zero when the clock appears to run backwards
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_timer_backwards_clock_clamps_to_zero() {
+        let mut timer = Timer::new();
+        // A start in the future simulates `now < start`.
+        timer.time_at("skewed", Instant::now() + Duration::from_secs(60));
+        assert_eq!(timer.time_log("skewed", true), 0.0);
+        assert_eq!(timer.snapshot_map()["skewed"], Duration::ZERO);
+        assert_eq!(timer.time_end("skewed", true), 0.0);
+        assert_eq!(timer.stats("skewed").unwrap().max_ms(), 0.0);
+    }
+
+    /// Tests the #[timed] attribute macro
+    #[test]
+    #[cfg(all(feature = "macros", not(target_arch = "wasm32")))]
+    fn test_timed_macro() {
+        #[crate::timed]
+        fn timed_sync(early: bool) -> u32 {
+            if early {
+                return 1;
+            }
+            sleep(Duration::from_millis(5));
+            2
+        }
+
+        #[crate::timed("timed_async_custom")]
+        async fn timed_async() -> u32 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            3
+        }
+
+        assert_eq!(timed_sync(true), 1);
+        assert_eq!(timed_sync(false), 2);
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        assert_eq!(runtime.block_on(timed_async()), 3);
+
+        let global = SharedTimer::global();
+        assert_eq!(global.stats("timed_sync").unwrap().count(), 2);
+        let stats = global.stats("timed_async_custom").unwrap();
+        assert_eq!(stats.count(), 1);
+        assert!(stats.max_ms() >= 5.0);
+    }
+
+    /// Tests that #[timed] functions are safe to call recursively and from several threads
+    #[test]
+    #[cfg(all(feature = "macros", not(target_arch = "wasm32")))]
+    fn test_timed_macro_recursive_and_threaded() {
+        #[crate::timed("timed_recursive")]
+        fn countdown(n: u32) -> u32 {
+            if n == 0 {
+                return 0;
+            }
+            1 + countdown(n - 1)
+        }
+
+        #[crate::timed("timed_threaded")]
+        fn work() {
+            sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(countdown(3), 3);
+        assert_eq!(
+            SharedTimer::global()
+                .stats("timed_recursive")
+                .unwrap()
+                .count(),
+            1
+        );
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| std::thread::spawn(|| (0..10).for_each(|_| work())))
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let stats = SharedTimer::global().stats("timed_threaded").unwrap();
+        assert!((1..=40).contains(&stats.count()));
     }
 
     /// Tests Timer::duration_to_ms() method
@@ -285,3 +6444,23 @@ mod tests {
         assert_eq!(Timer::duration_to_ms(duration), 1234.0);
     }
 }
+
+/// Test module for the `disabled` feature
+#[cfg(all(test, feature = "disabled"))]
+mod disabled_tests {
+    use super::*;
+
+    /// Tests that the disabled feature turns timing into no-ops
+    #[test]
+    fn test_timer_disabled() {
+        let mut timer = Timer::new();
+        timer.time("disabled");
+        assert!(timer.timers.is_empty());
+        assert_eq!(timer.time_log("disabled", true), 0.0);
+        assert_eq!(timer.time_end("disabled", true), 0.0);
+        assert!(timer.stats("disabled").is_none());
+        let mut calls = 0;
+        assert_eq!(timer.bench("disabled", 3, || calls += 1).count(), 0);
+        assert_eq!(calls, 0);
+    }
+}
@@ -0,0 +1,50 @@
+//! A mutex guard wrapper that times how long a lock is held.
+
+use crate::SharedTimer;
+use std::ops::{Deref, DerefMut};
+use std::sync::MutexGuard;
+
+/// A `MutexGuard` that records how long it is held, created by `SharedTimer::guard_lock`.
+///
+/// The timer starts when the guard is wrapped and ends when the wrapper is dropped, right
+/// after the lock is released. The wrapper dereferences to the protected data.
+pub struct TimedMutexGuard<'a, T> {
+    /// The wrapped guard, only taken on drop to release the lock before the timer ends.
+    guard: Option<MutexGuard<'a, T>>,
+    timer: SharedTimer,
+    label: String,
+}
+
+impl<'a, T> TimedMutexGuard<'a, T> {
+    /// Starts a timer for `label` and wraps `guard`.
+    pub(crate) fn new(timer: SharedTimer, label: &str, guard: MutexGuard<'a, T>) -> Self {
+        timer.time(label);
+        TimedMutexGuard {
+            guard: Some(guard),
+            timer,
+            label: label.to_string(),
+        }
+    }
+}
+
+impl<T> Deref for TimedMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.guard.as_ref().expect("guard is only taken on drop")
+    }
+}
+
+impl<T> DerefMut for TimedMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.guard.as_mut().expect("guard is only taken on drop")
+    }
+}
+
+impl<T> Drop for TimedMutexGuard<'_, T> {
+    /// Releases the lock, then ends the timer and prints the hold time.
+    fn drop(&mut self) {
+        drop(self.guard.take());
+        self.timer.time_end(&self.label, false);
+    }
+}
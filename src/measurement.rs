@@ -0,0 +1,58 @@
+//! Self-contained measurements that don't touch a `Timer`'s map.
+
+use crate::ENABLED;
+use std::time::{Duration, Instant};
+
+/// A running measurement, created by `Timer::start_measurement`.
+///
+/// The measurement owns its start instant, so it can be moved around and stopped anywhere
+/// without any `Timer` bookkeeping.
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    label: String,
+    /// The start instant, or `None` when timing is compiled out by the `disabled` feature.
+    start: Option<Instant>,
+}
+
+impl Measurement {
+    /// Starts a measurement for `label`.
+    pub(crate) fn start(label: &str) -> Self {
+        Measurement {
+            label: label.to_string(),
+            start: ENABLED.then(Instant::now),
+        }
+    }
+
+    /// Returns the label of the measurement.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Stops the measurement.
+    ///
+    /// # Returns
+    ///
+    /// Returns the completed measurement with its label and elapsed time.
+    pub fn stop(self) -> CompletedMeasurement {
+        CompletedMeasurement {
+            elapsed: self.start.map_or(Duration::ZERO, |start| start.elapsed()),
+            label: self.label,
+        }
+    }
+}
+
+/// A stopped measurement, returned by `Measurement::stop`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletedMeasurement {
+    /// The label of the measurement.
+    pub label: String,
+    /// The time between starting and stopping the measurement.
+    pub elapsed: Duration,
+}
+
+impl CompletedMeasurement {
+    /// Returns the elapsed time in milliseconds.
+    pub fn elapsed_ms(&self) -> f64 {
+        self.elapsed.as_secs_f64() * 1000.0
+    }
+}
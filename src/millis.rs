@@ -0,0 +1,115 @@
+//! A typed millisecond value, returned by the `*_typed` methods of `Timer`.
+
+use std::fmt;
+use std::ops::{Add, Deref, Sub};
+
+/// An elapsed time in milliseconds.
+///
+/// The raw-`f64` methods leave the unit implicit; `Millis` makes it part of the type while
+/// still dereferencing to the `f64` for arithmetic and comparisons.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Millis(pub f64);
+
+impl Millis {
+    /// Returns the value in milliseconds.
+    pub fn get(self) -> f64 {
+        self.0
+    }
+
+    /// Returns the value in seconds.
+    pub fn as_secs(self) -> f64 {
+        self.0 / 1000.0
+    }
+
+    /// Formats the value in a unit chosen at the call site, with three decimals.
+    ///
+    /// This leaves the timer's own output untouched, e.g.
+    /// `timer.time_end_typed("parse", true).display_as(TimeUnit::Micros)` gives `1500.000µs`
+    /// for 1.5 milliseconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `unit` - The unit to format the value in.
+    ///
+    /// # Returns
+    ///
+    /// Returns the formatted value followed by the unit's suffix.
+    pub fn display_as(self, unit: TimeUnit) -> String {
+        let value = match unit {
+            TimeUnit::Nanos => self.0 * 1_000_000.0,
+            TimeUnit::Micros => self.0 * 1000.0,
+            TimeUnit::Millis => self.0,
+            TimeUnit::Secs => self.as_secs(),
+        };
+        format!("{:.3}{}", value, unit.suffix())
+    }
+}
+
+/// A unit to display an elapsed time in, used by `Millis::display_as`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    /// Nanoseconds, suffixed `ns`.
+    Nanos,
+    /// Microseconds, suffixed `µs`.
+    Micros,
+    /// Milliseconds, suffixed `ms`.
+    Millis,
+    /// Seconds, suffixed `s`.
+    Secs,
+}
+
+impl TimeUnit {
+    /// Returns the suffix printed after values in this unit.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            TimeUnit::Nanos => "ns",
+            TimeUnit::Micros => "µs",
+            TimeUnit::Millis => "ms",
+            TimeUnit::Secs => "s",
+        }
+    }
+}
+
+impl Deref for Millis {
+    type Target = f64;
+
+    fn deref(&self) -> &f64 {
+        &self.0
+    }
+}
+
+impl From<f64> for Millis {
+    fn from(ms: f64) -> Self {
+        Millis(ms)
+    }
+}
+
+impl From<Millis> for f64 {
+    fn from(ms: Millis) -> Self {
+        ms.0
+    }
+}
+
+impl Add for Millis {
+    type Output = Millis;
+
+    fn add(self, rhs: Millis) -> Millis {
+        Millis(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Millis {
+    type Output = Millis;
+
+    fn sub(self, rhs: Millis) -> Millis {
+        Millis(self.0 - rhs.0)
+    }
+}
+
+impl fmt::Display for Millis {
+    /// Formats the value as `12.345ms`, honoring an explicit precision such as `{:.1}`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = f.precision().unwrap_or(3);
+        write!(f, "{:.*}ms", precision, self.0)
+    }
+}
@@ -0,0 +1,162 @@
+//! Destination of the lines printed by `Timer`, with optional batching.
+
+use crate::in_namespace;
+use std::io::Write;
+
+/// Where printed lines go and how they are batched.
+#[derive(Default)]
+pub(crate) struct Output {
+    /// Custom destination for printed lines; stdout is used when unset.
+    writer: Option<Box<dyn Write + Send>>,
+    /// Destinations for the lines of single labels and dotted label subtrees.
+    routes: Vec<Route>,
+    /// Lines waiting to be written while buffering is enabled, with the index of their route.
+    buffer: Vec<(Option<usize>, String)>,
+    /// Number of buffered lines that triggers a flush, or `None` to write lines immediately.
+    threshold: Option<usize>,
+}
+
+/// A destination for the lines of a single label or a dotted label subtree.
+struct Route {
+    /// The label, or the root of the label subtree.
+    label: String,
+    /// Whether only `label` itself is routed, not its subtree.
+    exact: bool,
+    writer: Box<dyn Write + Send>,
+}
+
+impl Route {
+    /// Returns whether the route covers `label`.
+    fn matches(&self, label: &str) -> bool {
+        if self.exact {
+            self.label == label
+        } else {
+            in_namespace(label, &self.label)
+        }
+    }
+}
+
+impl Output {
+    /// Sets the destination for printed lines, flushing pending lines to the old one first.
+    pub(crate) fn set_writer(&mut self, writer: Option<Box<dyn Write + Send>>) {
+        self.flush();
+        self.writer = writer;
+    }
+
+    /// Sends the lines of a label, or with `exact` unset of its whole subtree, to their own
+    /// destination, flushing pending lines first. Routing the same label again replaces its
+    /// writer.
+    pub(crate) fn route(&mut self, label: &str, exact: bool, writer: Box<dyn Write + Send>) {
+        self.flush();
+        match self
+            .routes
+            .iter_mut()
+            .find(|route| route.label == label && route.exact == exact)
+        {
+            Some(route) => route.writer = writer,
+            None => self.routes.push(Route {
+                label: label.to_string(),
+                exact,
+                writer,
+            }),
+        }
+    }
+
+    /// Returns the index of the route for `label`: an exact route if there is one, otherwise
+    /// the subtree route with the longest prefix.
+    fn route_index(&self, label: &str) -> Option<usize> {
+        self.routes
+            .iter()
+            .enumerate()
+            .filter(|(_, route)| route.matches(label))
+            .max_by_key(|(_, route)| (route.exact, route.label.len()))
+            .map(|(index, _)| index)
+    }
+
+    /// Sets the buffering threshold, flushing pending lines if buffering is turned off.
+    pub(crate) fn set_threshold(&mut self, threshold: Option<usize>) {
+        self.threshold = threshold;
+        if threshold.is_none() {
+            self.flush();
+        }
+    }
+
+    /// Returns the number of lines waiting to be written.
+    pub(crate) fn pending(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Writes a line, or buffers it if buffering is enabled.
+    pub(crate) fn emit(&mut self, line: String) {
+        self.emit_to(None, line);
+    }
+
+    /// Writes a line about a label to its prefix's route, falling back to the default
+    /// destination, or buffers it if buffering is enabled.
+    pub(crate) fn emit_for(&mut self, label: &str, line: String) {
+        let route = self.route_index(label);
+        self.emit_to(route, line);
+    }
+
+    fn emit_to(&mut self, route: Option<usize>, line: String) {
+        match self.threshold {
+            Some(threshold) => {
+                self.buffer.push((route, line));
+                if self.buffer.len() >= threshold {
+                    self.flush();
+                }
+            }
+            None => {
+                self.write_lines(std::iter::once((route, line)));
+                let writer = match route {
+                    Some(index) => Some(&mut self.routes[index].writer),
+                    None => self.writer.as_mut(),
+                };
+                if let Some(writer) = writer {
+                    let _ = writer.flush();
+                }
+            }
+        }
+    }
+
+    /// Writes all buffered lines in one batch.
+    pub(crate) fn flush(&mut self) {
+        let lines = std::mem::take(&mut self.buffer);
+        self.write_lines(lines);
+        for route in &mut self.routes {
+            let _ = route.writer.flush();
+        }
+        match self.writer.as_mut() {
+            Some(writer) => {
+                let _ = writer.flush();
+            }
+            None => {
+                let _ = std::io::stdout().flush();
+            }
+        }
+    }
+
+    fn write_lines(&mut self, lines: impl IntoIterator<Item = (Option<usize>, String)>) {
+        // A single `print!` takes the stdout lock once for the whole batch.
+        let mut batch = String::new();
+        for (route, line) in lines {
+            match route {
+                Some(index) => {
+                    let _ = writeln!(self.routes[index].writer, "{}", line);
+                }
+                None => match self.writer.as_mut() {
+                    Some(writer) => {
+                        let _ = writeln!(writer, "{}", line);
+                    }
+                    None => {
+                        batch.push_str(&line);
+                        batch.push('\n');
+                    }
+                },
+            }
+        }
+        if !batch.is_empty() {
+            print!("{}", batch);
+        }
+    }
+}
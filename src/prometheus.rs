@@ -0,0 +1,27 @@
+//! Helpers for the OpenMetrics text exposition format used by the `prometheus` feature.
+
+/// The metric family every timer is exported under.
+pub(crate) const METRIC: &str = "timelog_duration_seconds";
+
+/// Escapes a label value for the OpenMetrics text format.
+pub(crate) fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Formats a bucket bound in seconds, using `+Inf` for the final bucket.
+pub(crate) fn bound(seconds: f64) -> String {
+    if seconds.is_infinite() {
+        "+Inf".to_string()
+    } else {
+        format!("{}", seconds)
+    }
+}
@@ -0,0 +1,80 @@
+//! A streaming quantile estimator used by the `approx-quantiles` feature.
+
+use std::collections::BTreeMap;
+
+/// Relative accuracy of estimated quantiles.
+const RELATIVE_ACCURACY: f64 = 0.01;
+
+/// Values at or below this many milliseconds are counted as zero.
+const MIN_MS: f64 = 1e-9;
+
+/// A log-bucketed quantile sketch in the style of DDSketch.
+///
+/// Each measurement increments the bucket covering its value, where bucket bounds grow
+/// geometrically, so any estimated quantile is within `RELATIVE_ACCURACY` of a recorded value
+/// of that rank. Memory depends only on the range of values seen, not on their count: about
+/// 115 buckets per power of ten.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct QuantileSketch {
+    /// Counts of measurements, keyed by bucket index.
+    buckets: BTreeMap<i32, u64>,
+    /// Number of measurements at or below `MIN_MS`.
+    zeros: u64,
+    /// Total number of measurements.
+    count: u64,
+}
+
+impl QuantileSketch {
+    /// Returns the ratio between the bounds of a bucket.
+    fn gamma() -> f64 {
+        (1.0 + RELATIVE_ACCURACY) / (1.0 - RELATIVE_ACCURACY)
+    }
+
+    /// Folds a single measurement into the sketch.
+    pub(crate) fn record(&mut self, ms: f64) {
+        self.count += 1;
+        if ms.is_nan() || ms <= MIN_MS {
+            self.zeros += 1;
+            return;
+        }
+        let index = (ms.ln() / Self::gamma().ln()).ceil() as i32;
+        *self.buckets.entry(index).or_default() += 1;
+    }
+
+    /// Merges another sketch into this one.
+    pub(crate) fn merge(&mut self, other: &QuantileSketch) {
+        for (&index, &count) in &other.buckets {
+            *self.buckets.entry(index).or_default() += count;
+        }
+        self.zeros += other.zeros;
+        self.count += other.count;
+    }
+
+    /// Estimates a quantile.
+    ///
+    /// # Arguments
+    ///
+    /// * `q` - The quantile, between 0.0 and 1.0.
+    ///
+    /// # Returns
+    ///
+    /// Returns the estimated value in milliseconds, or `None` if nothing was recorded.
+    pub(crate) fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = (q.clamp(0.0, 1.0) * (self.count - 1) as f64).round() as u64;
+        if rank < self.zeros {
+            return Some(0.0);
+        }
+        let gamma = Self::gamma();
+        let mut seen = self.zeros;
+        for (&index, &count) in &self.buckets {
+            seen += count;
+            if seen > rank {
+                return Some(2.0 * gamma.powi(index) / (gamma + 1.0));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,100 @@
+//! RAII scope guards that time a block of code and track nesting per timer.
+
+use crate::{SharedTimer, Timer};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::ops::{Deref, DerefMut};
+
+/// A guard that times the enclosing scope, created by `Timer::scope`.
+///
+/// The timer is ended and its runtime printed when the guard is dropped. The guard
+/// dereferences to the `Timer`, so nested scopes are opened through it.
+pub struct ScopeGuard<'a, S: BuildHasher = RandomState> {
+    timer: &'a mut Timer<S>,
+    label: String,
+    /// The position of the guard's key on the timer's scope stack, if its timer was started.
+    index: Option<usize>,
+    /// The enclosing timer the runtime is reported as a share of, set by `Timer::scope_within`.
+    parent: Option<String>,
+}
+
+impl<'a, S: BuildHasher> ScopeGuard<'a, S> {
+    /// Wraps a timer started by `Timer::enter_scope`, whose key is at `index` on the stack.
+    pub(crate) fn new(timer: &'a mut Timer<S>, label: &str, index: Option<usize>) -> Self {
+        ScopeGuard {
+            timer,
+            label: label.to_string(),
+            index,
+            parent: None,
+        }
+    }
+
+    /// Like `new`, but the runtime is printed as a share of `parent`'s elapsed time on drop.
+    pub(crate) fn within(
+        timer: &'a mut Timer<S>,
+        label: &str,
+        index: Option<usize>,
+        parent: &str,
+    ) -> Self {
+        let mut guard = Self::new(timer, label, index);
+        guard.parent = Some(parent.to_string());
+        guard
+    }
+
+    /// Returns the label of the timer this guard ends on drop.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+impl<S: BuildHasher> Deref for ScopeGuard<'_, S> {
+    type Target = Timer<S>;
+
+    fn deref(&self) -> &Timer<S> {
+        self.timer
+    }
+}
+
+impl<S: BuildHasher> DerefMut for ScopeGuard<'_, S> {
+    fn deref_mut(&mut self) -> &mut Timer<S> {
+        self.timer
+    }
+}
+
+impl<S: BuildHasher> Drop for ScopeGuard<'_, S> {
+    /// Pops the guard off the scope stack, then ends the timer and prints its runtime.
+    fn drop(&mut self) {
+        if let Some(index) = self.index {
+            self.timer.leave_scope(index);
+        }
+        match &self.parent {
+            Some(parent) => self.timer.time_end_within(&self.label, parent),
+            None => self.timer.time_end(&self.label, false),
+        };
+    }
+}
+
+/// A guard that times a function call in `SharedTimer::global()`.
+///
+/// Created by the code the `#[timed]` attribute generates; not meant to be used directly.
+#[doc(hidden)]
+pub struct TimedGuard {
+    timer: SharedTimer,
+    label: &'static str,
+}
+
+impl TimedGuard {
+    /// Starts the global timer for `label`.
+    pub fn new(label: &'static str) -> Self {
+        let timer = SharedTimer::global();
+        timer.time(label);
+        TimedGuard { timer, label }
+    }
+}
+
+impl Drop for TimedGuard {
+    /// Ends the global timer and prints its runtime.
+    fn drop(&mut self) {
+        self.timer.time_end(self.label, false);
+    }
+}
@@ -0,0 +1,118 @@
+//! Sessions that group the timers of one unit of work, e.g. a request.
+
+use crate::{SharedTimer, TimerStats};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Source of unique session prefixes, shared by all timers.
+static NEXT_SESSION: AtomicU64 = AtomicU64::new(0);
+
+/// A group of timers reported and cleared together, created by `SharedTimer::session`.
+///
+/// Labels are namespaced under a unique prefix such as `session_3`, so `time("parse")` starts
+/// `session_3.parse` in the shared timer and concurrent sessions never see each other's
+/// timers. The session's timers and statistics are removed from the shared timer when the
+/// session is finished or dropped.
+pub struct Session {
+    timer: SharedTimer,
+    prefix: String,
+}
+
+impl Session {
+    /// Creates a session with a fresh prefix.
+    pub(crate) fn new(timer: SharedTimer) -> Self {
+        let id = NEXT_SESSION.fetch_add(1, Ordering::Relaxed);
+        Session {
+            timer,
+            prefix: format!("session_{}", id),
+        }
+    }
+
+    /// Returns the prefix the session's labels are namespaced under.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Starts a new timer in the session. See `Timer::time`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer, relative to the session.
+    pub fn time(&self, label: &str) {
+        self.timer.time(&self.label(label));
+    }
+
+    /// Logs and prints the current time of a session timer. See `Timer::time_log`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer, relative to the session.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    pub fn time_log(&self, label: &str, silent: bool) -> f64 {
+        self.timer.time_log(&self.label(label), silent)
+    }
+
+    /// Ends a session timer and prints its runtime. See `Timer::time_end`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer, relative to the session.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    pub fn time_end(&self, label: &str, silent: bool) -> f64 {
+        self.timer.time_end(&self.label(label), silent)
+    }
+
+    /// Returns a copy of the accumulated statistics of a session label.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer, relative to the session.
+    ///
+    /// # Returns
+    ///
+    /// Returns the label's statistics, or `None` if the label has never been ended.
+    pub fn stats(&self, label: &str) -> Option<TimerStats> {
+        self.timer.stats(&self.label(label))
+    }
+
+    /// Builds a report of the session's accumulated statistics, like `Timer::report`.
+    ///
+    /// Labels are shown relative to the session, and shares are of the session's total.
+    ///
+    /// # Returns
+    ///
+    /// Returns the report as a string, one line per label, or an empty string if no session
+    /// timer has been ended.
+    pub fn report(&self) -> String {
+        self.timer
+            .with_timer(|timer| timer.report_namespace(&self.prefix))
+    }
+
+    /// Ends the session, removing its timers and statistics from the shared timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the session's final report.
+    pub fn finish(self) -> String {
+        self.report()
+    }
+
+    /// Returns the full label of a session-relative label.
+    fn label(&self, label: &str) -> String {
+        format!("{}.{}", self.prefix, label)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.timer
+            .with_timer(|timer| timer.clear_prefix(&self.prefix));
+    }
+}
@@ -0,0 +1,336 @@
+//! A `Timer` shared across threads behind a mutex.
+
+use crate::{Session, TimedMutexGuard, Timer, TimerStats, Timing};
+use std::collections::HashMap;
+#[cfg(all(feature = "self-metrics", not(target_arch = "wasm32")))]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::OnceLock;
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(all(feature = "self-metrics", not(target_arch = "wasm32")))]
+use std::time::Instant;
+
+/// A cloneable, thread-safe handle to a `Timer`.
+///
+/// Every operation takes the internal lock, so prefer a `LocalTimer` on single-threaded hot
+/// paths. Clones share the same timers and statistics.
+#[derive(Clone, Default)]
+pub struct SharedTimer {
+    inner: Arc<Mutex<Timer>>,
+    /// Total time spent waiting to acquire `inner`, in nanoseconds.
+    #[cfg(all(feature = "self-metrics", not(target_arch = "wasm32")))]
+    lock_wait_ns: Arc<AtomicU64>,
+}
+
+impl SharedTimer {
+    /// Creates a new `SharedTimer` instance.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `SharedTimer` wrapping an empty `Timer`.
+    pub fn new() -> Self {
+        Self::from_timer(Timer::new())
+    }
+
+    /// Wraps an existing `Timer`.
+    ///
+    /// # Arguments
+    ///
+    /// * `timer` - The timer to share.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `SharedTimer` owning `timer`.
+    // On wasm32, `Timer` holds a `Performance` handle and isn't `Send`, but there are no
+    // threads to share it with either, so the `Arc` is only used for cheap clones.
+    #[cfg_attr(target_arch = "wasm32", allow(clippy::arc_with_non_send_sync))]
+    pub fn from_timer(timer: Timer) -> Self {
+        SharedTimer {
+            inner: Arc::new(Mutex::new(timer)),
+            #[cfg(all(feature = "self-metrics", not(target_arch = "wasm32")))]
+            lock_wait_ns: Arc::default(),
+        }
+    }
+
+    /// Returns a handle to the process-wide timer that `#[timed]` functions record into.
+    ///
+    /// The global timer is created on first use in reentrant mode (see `Timer::set_reentrant`),
+    /// so recursive calls of a timed function are measured once, by the outermost call.
+    /// Handles are cheap clones sharing the same timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `SharedTimer` backed by the global timer.
+    pub fn global() -> SharedTimer {
+        fn create() -> SharedTimer {
+            let mut timer = Timer::new();
+            timer.set_reentrant(true);
+            SharedTimer::from_timer(timer)
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            static GLOBAL: OnceLock<SharedTimer> = OnceLock::new();
+            GLOBAL.get_or_init(create).clone()
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            thread_local! {
+                static GLOBAL: SharedTimer = create();
+            }
+            GLOBAL.with(SharedTimer::clone)
+        }
+    }
+
+    /// Starts a new timer. See `Timer::time`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    pub fn time(&self, label: &str) {
+        self.lock().time(label);
+    }
+
+    /// Starts a new timer under an automatically generated label. See `Timer::time_auto`.
+    ///
+    /// The counter is shared by all clones, so labels are unique across threads.
+    ///
+    /// # Returns
+    ///
+    /// Returns the generated label, to pass to `time_end` later.
+    pub fn time_auto(&self) -> String {
+        self.lock().time_auto()
+    }
+
+    /// Logs and prints the current time of a timer without stopping it. See `Timer::time_log`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    pub fn time_log(&self, label: &str, silent: bool) -> f64 {
+        self.lock().time_log(label, silent)
+    }
+
+    /// Ends a timer and prints its runtime. See `Timer::time_end`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    pub fn time_end(&self, label: &str, silent: bool) -> f64 {
+        self.lock().time_end(label, silent)
+    }
+
+    /// Starts a new timer unless another thread holds the lock. See `Timer::time`.
+    ///
+    /// This never blocks, which bounds the overhead on latency-critical paths; the timer is
+    /// simply not started when the lock is contended.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the timer was started, or `false` if the lock was contended.
+    pub fn try_time(&self, label: &str) -> bool {
+        match self.try_lock() {
+            Some(mut timer) => {
+                timer.time(label);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ends a timer unless another thread holds the lock. See `Timer::time_end`.
+    ///
+    /// This never blocks; when the lock is contended, the timer keeps running.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running (0.0 if the timer doesn't
+    /// exist), or `None` if the lock was contended.
+    pub fn try_time_end(&self, label: &str, silent: bool) -> Option<f64> {
+        Some(self.try_lock()?.time_end(label, silent))
+    }
+
+    /// Starts a batch of timers while holding the lock once. See `Timer::time_many`.
+    ///
+    /// # Arguments
+    ///
+    /// * `labels` - The labels of the timers to start.
+    pub fn time_many(&self, labels: &[&str]) {
+        self.lock().time_many(labels);
+    }
+
+    /// Ends a batch of timers while holding the lock once. See `Timer::time_end_many`.
+    ///
+    /// # Arguments
+    ///
+    /// * `labels` - The labels of the timers to end.
+    ///
+    /// # Returns
+    ///
+    /// Returns the elapsed milliseconds for each label, in the same order as `labels`.
+    pub fn time_end_many(&self, labels: &[&str]) -> Vec<f64> {
+        self.lock().time_end_many(labels)
+    }
+
+    /// Returns a copy of the accumulated statistics of a label. See `Timer::stats`.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the label's statistics, or `None` if the label has never been ended.
+    pub fn stats(&self, label: &str) -> Option<TimerStats> {
+        self.lock().stats(label).copied()
+    }
+
+    /// Returns the accumulated statistics of every label and resets them while holding the
+    /// lock once. See `Timer::drain_stats`.
+    ///
+    /// # Returns
+    ///
+    /// Returns a map from each label to its statistics since the last drain.
+    pub fn drain_stats(&self) -> HashMap<String, TimerStats> {
+        self.lock().drain_stats()
+    }
+
+    /// Returns a copy of every running timer's elapsed time. See `Timer::snapshot_map`.
+    ///
+    /// The lock is only held while the snapshot is taken.
+    ///
+    /// # Returns
+    ///
+    /// Returns a map from each running label to its elapsed `Duration`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn snapshot_map(&self) -> HashMap<String, Duration> {
+        self.lock().snapshot_map()
+    }
+
+    /// Times how long a mutex guard is held, to quantify lock contention in your own code.
+    ///
+    /// The timer for `label` starts now and ends when the returned wrapper is dropped, so
+    /// the hold time is recorded in the label's statistics like any other measurement.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the hold time.
+    /// * `guard` - The guard to wrap.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `TimedMutexGuard` that dereferences to the protected data.
+    pub fn guard_lock<'a, T>(
+        &self,
+        label: &str,
+        guard: MutexGuard<'a, T>,
+    ) -> TimedMutexGuard<'a, T> {
+        TimedMutexGuard::new(self.clone(), label, guard)
+    }
+
+    /// Starts a session that groups timers, e.g. those of one request in a server.
+    ///
+    /// Sessions namespace their labels under a unique prefix, so concurrent sessions never
+    /// see each other's timers. See `Session`.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new `Session` sharing this timer.
+    pub fn session(&self) -> Session {
+        Session::new(self.clone())
+    }
+
+    /// Runs a closure with exclusive access to the underlying `Timer`.
+    ///
+    /// This gives access to the full `Timer` API while holding the lock once.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The closure to run.
+    ///
+    /// # Returns
+    ///
+    /// Returns the closure's result.
+    pub fn with_timer<R>(&self, f: impl FnOnce(&mut Timer) -> R) -> R {
+        f(&mut self.lock())
+    }
+
+    /// Returns the total time spent waiting to acquire the internal lock, across all clones.
+    ///
+    /// Only contended acquisitions are measured, so uncontended operations stay as cheap as
+    /// without the `self-metrics` feature. A large value relative to the measured work suggests
+    /// switching to per-thread `LocalTimer`s.
+    ///
+    /// # Returns
+    ///
+    /// Returns the accumulated lock wait time in milliseconds.
+    #[cfg(all(feature = "self-metrics", not(target_arch = "wasm32")))]
+    pub fn self_overhead_ms(&self) -> f64 {
+        self.lock_wait_ns.load(Ordering::Relaxed) as f64 / 1_000_000.0
+    }
+
+    /// Locks the underlying `Timer` if no other thread holds it, recovering it if poisoned.
+    fn try_lock(&self) -> Option<MutexGuard<'_, Timer>> {
+        match self.inner.try_lock() {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::WouldBlock) => None,
+            Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+        }
+    }
+
+    /// Locks the underlying `Timer`, recovering it if another thread panicked while holding it.
+    ///
+    /// Timers are best-effort instrumentation, so a panic elsewhere must not cascade into every
+    /// later timing call.
+    fn lock(&self) -> MutexGuard<'_, Timer> {
+        #[cfg(all(feature = "self-metrics", not(target_arch = "wasm32")))]
+        return match self.inner.try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) => {
+                let start = Instant::now();
+                let guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+                let waited = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+                self.lock_wait_ns.fetch_add(waited, Ordering::Relaxed);
+                guard
+            }
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        };
+        #[cfg(not(all(feature = "self-metrics", not(target_arch = "wasm32"))))]
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Timing for SharedTimer {
+    fn time(&mut self, label: &str) {
+        SharedTimer::time(self, label)
+    }
+
+    fn time_log(&self, label: &str, silent: bool) -> f64 {
+        SharedTimer::time_log(self, label, silent)
+    }
+
+    fn time_end(&mut self, label: &str, silent: bool) -> f64 {
+        SharedTimer::time_end(self, label, silent)
+    }
+}
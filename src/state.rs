@@ -0,0 +1,72 @@
+//! In-memory snapshots of a `Timer`'s state.
+
+#[cfg(feature = "approx-quantiles")]
+use crate::quantiles::QuantileSketch;
+use crate::TimerStats;
+use std::collections::HashMap;
+use std::panic::Location;
+use std::time::{Duration, Instant, SystemTime};
+
+/// A snapshot of a `Timer`'s state, produced by `Timer::save_state`.
+///
+/// The snapshot holds the running timers with their original start instants and everything
+/// attached to them (pauses, nesting depths, tags, call sites), the accumulated statistics,
+/// the retained samples and the counters. Instants are only meaningful within the process
+/// that captured them, so snapshots can't be persisted.
+#[derive(Debug, Clone, Default)]
+pub struct TimerState {
+    pub(crate) timers: HashMap<String, Instant>,
+    pub(crate) paused: HashMap<String, Instant>,
+    pub(crate) paused_total: HashMap<String, Duration>,
+    pub(crate) wall_starts: HashMap<String, SystemTime>,
+    pub(crate) depths: HashMap<String, usize>,
+    pub(crate) tags: HashMap<String, Vec<(String, String)>>,
+    pub(crate) locations: HashMap<String, &'static Location<'static>>,
+    pub(crate) categories: HashMap<String, String>,
+    pub(crate) last_logged: HashMap<String, f64>,
+    #[cfg(feature = "alloc-count")]
+    pub(crate) alloc_starts: HashMap<String, u64>,
+    pub(crate) stats: HashMap<String, TimerStats>,
+    pub(crate) samples: HashMap<String, Vec<f64>>,
+    #[cfg(feature = "approx-quantiles")]
+    pub(crate) quantiles: HashMap<String, QuantileSketch>,
+    pub(crate) counters: HashMap<String, u64>,
+}
+
+impl TimerState {
+    /// Returns the accumulated statistics of a label in the snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the label's statistics, or `None` if the label had never been ended.
+    pub fn stats(&self, label: &str) -> Option<&TimerStats> {
+        self.stats.get(label)
+    }
+
+    /// Returns whether a timer was running when the snapshot was taken.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    pub fn is_running(&self, label: &str) -> bool {
+        self.timers.contains_key(label)
+    }
+}
+
+/// A label whose mean latency regressed against a baseline, found by
+/// `Timer::compare_to_baseline`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    /// The label of the timer.
+    pub label: String,
+    /// The mean in milliseconds in the baseline.
+    pub baseline_mean_ms: f64,
+    /// The mean in milliseconds in the current run.
+    pub current_mean_ms: f64,
+    /// The increase of the mean relative to the baseline, in percent.
+    pub change_pct: f64,
+}
@@ -0,0 +1,155 @@
+//! Aggregated statistics for repeated measurements of the same label.
+
+#[cfg(feature = "binary")]
+use crate::binary;
+use crate::Timer;
+use std::time::Duration;
+
+/// Accumulated statistics for a label.
+///
+/// Every completed measurement of a label is folded into its `TimerStats`, so the
+/// aggregate can be inspected without retaining the individual samples.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TimerStats {
+    count: u64,
+    /// Exact sum of all measurements in nanoseconds, converted to milliseconds only when read,
+    /// so many short measurements don't accumulate floating-point error.
+    total_ns: u128,
+    min_ms: f64,
+    max_ms: f64,
+    /// Running mean maintained by Welford's online algorithm.
+    mean: f64,
+    /// Sum of squared deviations from the running mean (Welford's M2 accumulator).
+    m2: f64,
+}
+
+impl TimerStats {
+    /// Folds a single measurement into the statistics.
+    ///
+    /// The total is summed from the exact nanoseconds of `duration`; milliseconds are only
+    /// derived for the minimum, maximum and variance.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - The measured duration.
+    pub(crate) fn record(&mut self, duration: Duration) {
+        let ms = Timer::duration_to_ms(duration);
+        if self.count == 0 {
+            self.min_ms = ms;
+            self.max_ms = ms;
+        } else {
+            self.min_ms = self.min_ms.min(ms);
+            self.max_ms = self.max_ms.max(ms);
+        }
+        self.count += 1;
+        self.total_ns += duration.as_nanos();
+        let delta = ms - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (ms - self.mean);
+    }
+
+    /// Merges the statistics of another bucket, e.g. from another worker, into this one.
+    ///
+    /// The combined mean is weighted by count (total time over total count), never an average
+    /// of averages, and the variance is combined with Chan's parallel form of Welford's
+    /// algorithm, so merging gives the same result as recording every measurement here.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The statistics to merge in.
+    pub fn merge(&mut self, other: &TimerStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        let weight = self.count as f64 * other.count as f64 / count as f64;
+        self.mean =
+            (self.mean * self.count as f64 + other.mean * other.count as f64) / count as f64;
+        self.m2 += other.m2 + delta * delta * weight;
+        self.count = count;
+        self.total_ns += other.total_ns;
+        self.min_ms = self.min_ms.min(other.min_ms);
+        self.max_ms = self.max_ms.max(other.max_ms);
+    }
+
+    /// Appends the statistics to a binary report.
+    #[cfg(feature = "binary")]
+    pub(crate) fn encode(&self, out: &mut Vec<u8>) {
+        binary::put_u64(out, self.count);
+        binary::put_u128(out, self.total_ns);
+        for value in [self.min_ms, self.max_ms, self.mean, self.m2] {
+            binary::put_f64(out, value);
+        }
+    }
+
+    /// Reads statistics written by `encode`.
+    #[cfg(feature = "binary")]
+    pub(crate) fn decode(reader: &mut binary::Reader<'_>) -> std::io::Result<Self> {
+        Ok(TimerStats {
+            count: reader.u64()?,
+            total_ns: reader.u128()?,
+            min_ms: reader.f64()?,
+            max_ms: reader.f64()?,
+            mean: reader.f64()?,
+            m2: reader.f64()?,
+        })
+    }
+
+    /// Returns the number of recorded measurements.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the exact sum of all recorded measurements in nanoseconds.
+    pub fn total_ns(&self) -> u128 {
+        self.total_ns
+    }
+
+    /// Returns the sum of all recorded measurements in milliseconds.
+    pub fn total_ms(&self) -> f64 {
+        self.total_ns as f64 / 1_000_000.0
+    }
+
+    /// Returns the shortest recorded measurement in milliseconds, or 0.0 if there are none.
+    pub fn min_ms(&self) -> f64 {
+        self.min_ms
+    }
+
+    /// Returns the longest recorded measurement in milliseconds, or 0.0 if there are none.
+    pub fn max_ms(&self) -> f64 {
+        self.max_ms
+    }
+
+    /// Returns the mean of the recorded measurements in milliseconds, or 0.0 if there are none.
+    pub fn mean_ms(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.total_ms() / self.count as f64
+        }
+    }
+
+    /// Returns the sample variance of the recorded measurements in milliseconds squared.
+    ///
+    /// The variance is computed online with Welford's algorithm, so no samples are retained.
+    /// Returns 0.0 if fewer than two measurements were recorded.
+    pub fn variance_ms(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / (self.count - 1) as f64
+        }
+    }
+
+    /// Returns the sample standard deviation of the recorded measurements in milliseconds.
+    ///
+    /// Returns 0.0 if fewer than two measurements were recorded.
+    pub fn std_dev_ms(&self) -> f64 {
+        self.variance_ms().sqrt()
+    }
+}
@@ -0,0 +1,64 @@
+//! Fire-and-forget StatsD reporting used by the `statsd` feature.
+
+use std::net::UdpSocket;
+
+/// A non-blocking UDP client that sends timings to a StatsD agent.
+pub(crate) struct StatsdClient {
+    socket: UdpSocket,
+    prefix: String,
+}
+
+impl StatsdClient {
+    /// Creates a client sending to `addr` with every metric name prefixed by `prefix`.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the StatsD agent, e.g. `127.0.0.1:8125`.
+    /// * `prefix` - The metric name prefix. An empty prefix sends bare labels.
+    ///
+    /// # Returns
+    ///
+    /// Returns the client, or the I/O error raised while setting up the socket.
+    pub(crate) fn connect(addr: &str, prefix: &str) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.set_nonblocking(true)?;
+        socket.connect(addr)?;
+        Ok(StatsdClient {
+            socket,
+            prefix: prefix.to_string(),
+        })
+    }
+
+    /// Sends a timing metric. Send errors are ignored so the measured code is never affected.
+    ///
+    /// Tags are appended in the Datadog format, `|#key:value,key2:value2`. The `:`, `|`, `,`
+    /// and `#` separators are replaced with `_` in the label and tags so the line stays parseable.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `ms` - The elapsed time in milliseconds.
+    /// * `tags` - The key-value tags attached to the timer.
+    pub(crate) fn send_timing(&self, label: &str, ms: f64, tags: &[(String, String)]) {
+        let label = sanitize(label);
+        let mut line = if self.prefix.is_empty() {
+            format!("{}:{}|ms", label, ms)
+        } else {
+            format!("{}.{}:{}|ms", self.prefix, label, ms)
+        };
+        if !tags.is_empty() {
+            let tags: Vec<String> = tags
+                .iter()
+                .map(|(key, value)| format!("{}:{}", sanitize(key), sanitize(value)))
+                .collect();
+            line.push_str("|#");
+            line.push_str(&tags.join(","));
+        }
+        let _ = self.socket.send(line.as_bytes());
+    }
+}
+
+/// Replaces the characters that delimit fields in a statsd line with `_`.
+fn sanitize(text: &str) -> String {
+    text.replace([':', '|', ',', '#'], "_")
+}
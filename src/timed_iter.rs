@@ -0,0 +1,49 @@
+//! An iterator adapter that times each item it yields.
+
+use crate::{Timer, ENABLED};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::Instant;
+
+/// An iterator that records the time taken to produce each item, created by
+/// `Timer::timed_iter`.
+///
+/// Every call to `next` that yields an item is recorded as one measurement of the label, so
+/// the label's statistics hold the item count, total and mean per item. Nothing is printed.
+pub struct TimedIter<'a, I, S: BuildHasher = RandomState> {
+    timer: &'a mut Timer<S>,
+    label: String,
+    iter: I,
+}
+
+impl<'a, I, S: BuildHasher> TimedIter<'a, I, S> {
+    /// Wraps `iter`, recording its items under `label`.
+    pub(crate) fn new(timer: &'a mut Timer<S>, label: &str, iter: I) -> Self {
+        let label = timer.label_key(label).into_owned();
+        TimedIter { timer, label, iter }
+    }
+
+    /// Returns the label the items are recorded under.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+impl<I: Iterator, S: BuildHasher> Iterator for TimedIter<'_, I, S> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        if !ENABLED {
+            return self.iter.next();
+        }
+        let start_time = Instant::now();
+        let item = self.iter.next()?;
+        let duration = self.timer.scaled(start_time.elapsed());
+        self.timer.record(&self.label, duration);
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
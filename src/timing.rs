@@ -0,0 +1,82 @@
+//! The `Timing` trait shared by every timer flavour, and a no-op implementation.
+
+use crate::Timer;
+use std::hash::BuildHasher;
+
+/// The core timing operations, implemented by every timer type.
+///
+/// Generic code can accept `&mut impl Timing` to work with either a `LocalTimer` on
+/// single-threaded hot paths or a `SharedTimer` shared across threads. Tests can substitute
+/// a `NoopTimer` or their own recording fake.
+pub trait Timing {
+    /// Starts a new timer.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label for the timer.
+    fn time(&mut self, label: &str);
+
+    /// Logs and prints the current time of a timer without stopping it.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    fn time_log(&self, label: &str, silent: bool) -> f64;
+
+    /// Ends a timer and prints its runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the timer.
+    /// * `silent` - Whether to suppress printing the message.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of milliseconds the timer has been running, or 0.0 if the timer doesn't exist.
+    fn time_end(&mut self, label: &str, silent: bool) -> f64;
+}
+
+impl<S: BuildHasher> Timing for Timer<S> {
+    fn time(&mut self, label: &str) {
+        Timer::time(self, label)
+    }
+
+    fn time_log(&self, label: &str, silent: bool) -> f64 {
+        Timer::time_log(self, label, silent)
+    }
+
+    fn time_end(&mut self, label: &str, silent: bool) -> f64 {
+        Timer::time_end(self, label, silent)
+    }
+}
+
+/// A `Timing` implementation that records and prints nothing.
+///
+/// Useful to disable instrumentation, e.g. in release builds, without changing call sites
+/// that accept `impl Timing`. Every measurement is reported as 0.0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoopTimer;
+
+impl NoopTimer {
+    /// Creates a new `NoopTimer` instance.
+    pub fn new() -> Self {
+        NoopTimer
+    }
+}
+
+impl Timing for NoopTimer {
+    fn time(&mut self, _label: &str) {}
+
+    fn time_log(&self, _label: &str, _silent: bool) -> f64 {
+        0.0
+    }
+
+    fn time_end(&mut self, _label: &str, _silent: bool) -> f64 {
+        0.0
+    }
+}
@@ -0,0 +1,42 @@
+//! # timelog-macros
+//!
+//! Procedural macros for `timelog`, re-exported by `timelog` behind its `macros` feature.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, ItemFn, LitStr};
+
+/// Times every call of a function under its name, recording into `SharedTimer::global()`.
+///
+/// The timer is started on entry and ended when the call returns, including early returns,
+/// `?` and panics that unwind. For an `async fn`, the timer starts on the first poll and
+/// ends when the future completes or is dropped.
+///
+/// The label is the function's name, e.g. `load_config`. Pass a string to use another
+/// label, e.g. `#[timed("config::load")]`. Recursive calls share their label, so only the
+/// outermost call is measured. Calls overlapping on several threads share it too and are
+/// measured as one span, from the first start to the last end.
+///
+/// ```ignore
+/// use timelog::timed;
+///
+/// #[timed]
+/// fn load_config() {
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn timed(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut function = parse_macro_input!(item as ItemFn);
+    let label = if attr.is_empty() {
+        function.sig.ident.to_string()
+    } else {
+        parse_macro_input!(attr as LitStr).value()
+    };
+
+    let guard = quote! {
+        let __timelog_guard = ::timelog::TimedGuard::new(#label);
+    };
+    function.block.stmts.insert(0, parse_quote!(#guard));
+    quote!(#function).into()
+}